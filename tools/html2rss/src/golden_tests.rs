@@ -0,0 +1,2213 @@
+// Offline golden tests: run the extraction pipeline against saved pages in tests/fixtures/
+// through a map-backed Fetcher and compare the resulting items field by field.
+
+use super::*;
+use std::collections::HashMap;
+
+// Serves canned bodies keyed by absolute URL; anything else is a fetch error. HEAD answers
+// 200 text/html for known URLs and 404 otherwise, unless overridden with `with_head`.
+// Every request is logged as "GET url" / "HEAD url".
+struct MapFetcher {
+    pages: HashMap<String, String>,
+    heads: HashMap<String, (u16, Option<String>)>,
+    lengths: HashMap<String, u64>,
+    log: RefCell<Vec<String>>,
+}
+
+impl MapFetcher {
+    fn new(pages: &[(&str, &str)]) -> Self {
+        MapFetcher {
+            pages: pages.iter().map(|(u, b)| (u.to_string(), b.to_string())).collect(),
+            heads: HashMap::new(),
+            lengths: HashMap::new(),
+            log: RefCell::new(Vec::new()),
+        }
+    }
+
+    fn with_head(mut self, url: &str, status: u16, content_type: Option<&str>) -> Self {
+        self.heads.insert(url.to_string(), (status, content_type.map(str::to_string)));
+        self
+    }
+
+    // Content-Length reported by HEAD for `url`
+    fn with_length(mut self, url: &str, length: u64) -> Self {
+        self.lengths.insert(url.to_string(), length);
+        self
+    }
+}
+
+impl Fetcher for MapFetcher {
+    fn get(&self, url: &Url) -> Result<Page, Box<dyn Error>> {
+        self.log.borrow_mut().push(format!("GET {}", url));
+        match self.pages.get(url.as_str()) {
+            Some(body) => Ok(Page { url: url.clone(), body: body.clone(), content_type: None }),
+            None => Err(format!("no fixture for {}", url).into()),
+        }
+    }
+
+    fn head(&self, url: &Url) -> Result<Head, Box<dyn Error>> {
+        self.log.borrow_mut().push(format!("HEAD {}", url));
+        let (status, content_type) = self.heads.get(url.as_str()).cloned().unwrap_or_else(|| {
+            if self.pages.contains_key(url.as_str()) { (200, Some("text/html".to_string())) } else { (404, None) }
+        });
+        Ok(Head { status, content_type, content_length: self.lengths.get(url.as_str()).copied() })
+    }
+}
+
+fn init_opts() {
+    OPTIONS.get_or_init(|| Args::parse_from(["html2rss", "--article-query-param", "t", "https://fixtures.invalid/"]));
+}
+
+fn fixture(name: &str) -> String {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures").join(name);
+    fs::read_to_string(&path).unwrap_or_else(|e| panic!("reading {}: {}", path.display(), e))
+}
+
+fn url(s: &str) -> Url {
+    Url::parse(s).unwrap()
+}
+
+#[test]
+fn title_case_keeps_acronyms() {
+    let smart = |t: &str| apply_title_case(t, TitleCase::Smart);
+    let lower = |t: &str| apply_title_case(t, TitleCase::Lower);
+    // all caps
+    assert_eq!(smart("NASA'S NEW ROCKET LAUNCHES FROM FLORIDA"), "NASA's New Rocket Launches From Florida");
+    assert_eq!(smart("CNN AND NFL REACH A STREAMING DEAL"), "CNN and NFL Reach a Streaming Deal");
+    assert_eq!(smart("MR. SMITH GOES TO WASHINGTON"), "Mr. Smith Goes to Washington");
+    assert_eq!(smart("DR. JONES TAKES ON THE FTC"), "Dr. Jones Takes on the FTC");
+    assert_eq!(lower("FBI OPENS INQUIRY INTO US BANK"), "FBI opens inquiry into US bank");
+    // mixed case: smart leaves it alone, lower keeps acronyms and brand names
+    assert_eq!(smart("iPhone Sales Beat the Forecast"), "iPhone Sales Beat the Forecast");
+    assert_eq!(lower("Apple's iPhone Faces The EU's New Rules"), "Apple's iPhone faces the EU's new rules");
+    assert_eq!(lower("NASA'S Budget: What It Means"), "NASA's budget: What it means");
+}
+
+#[test]
+fn json_ld_graph_articles() {
+    init_opts();
+    let base = url("https://www.example.com/world/");
+    let doc = Html::parse_document(&fixture("jsonld_graph.html"));
+    let items = extract_from_json_ld(&doc, &base).expect("JSON-LD items");
+    assert_eq!(items, vec![
+        Item {
+            title: "Harbour Bridge Reopens After Two-Year Restoration".into(),
+            link: "https://www.example.com/2024/03/14/harbour-bridge-reopens".into(),
+            description: Some("The landmark crossing welcomed traffic again on Thursday morning.".into()),
+            pub_date: Some("2024-03-14T08:30:00Z".into()),
+            updated: Some("2024-03-14T08:30:00Z".into()),
+            image: Some("https://www.example.com/img/bridge-1.jpg".into()),
+            images: vec!["https://www.example.com/img/bridge-1.jpg".into(), "https://www.example.com/img/bridge-2.jpg".into()],
+            ..Default::default()
+        },
+        Item {
+            title: "City Council Approves New Cycling Lanes".into(),
+            link: "https://www.example.com/2024/03/13/cycling-lanes-approved".into(),
+            description: Some("Twelve kilometres of protected lanes will be built by next summer.".into()),
+            pub_date: Some("2024-03-13T17:05:00+01:00".into()),
+            updated: Some("2024-03-13T17:05:00+01:00".into()),
+            image: Some("https://www.example.com/img/bikes.jpg".into()),
+            images: vec!["https://www.example.com/img/bikes.jpg".into()],
+            ..Default::default()
+        },
+    ]);
+}
+
+#[test]
+fn json_ld_start_page_items_filtered() {
+    init_opts();
+    let base = url("https://www.example.com/");
+    let article = |headline: &str, path: &str, date: &str, image: Option<&str>| serde_json::json!({
+        "@type": "NewsArticle", "headline": headline, "url": path,
+        "datePublished": format!("2024-03-{}T08:00:00Z", date), "image": image,
+    });
+    let graph = serde_json::json!({ "@context": "https://schema.org", "@graph": [
+        article("Harbour Bridge Reopens", "/2024/03/14/harbour-bridge", "14", Some("/img/bridge.jpg")),
+        article("Café Owners Welcome Ferries", "/2024/03/13/cafe-ferries", "13", Some("/img/cafe.jpg")),
+        article("Mayor Says \u{FFFD}No\u{FFFD} to Tower Plans", "/2024/03/12/tower-plans", "12", Some("/img/tower.jpg")),
+        article("Cycling Lanes Approved", "/2024/03/11/cycling-lanes", "11", None),
+        article("Tip Line", "mailto:tips@example.com", "10", Some("/img/tips.jpg")),
+        article("Harbour bridge reopens", "/2024/03/09/harbour-bridge-photos", "09", Some("/img/bridge-2.jpg")),
+    ]});
+    let doc = Html::parse_document(&format!(
+        r#"<html><head><script type="application/ld+json">{}</script></head><body></body></html>"#, graph));
+    let links = |filter: ItemFilter| start_page_json_ld_items(&doc, &base, filter).unwrap()
+        .into_iter().map(|it| it.link).collect::<Vec<_>>();
+
+    // the mailto: link goes whatever the flags; --require-image and --strict-encoding take one each
+    assert_eq!(links(ItemFilter::from_opts()).len(), 5);
+    assert_eq!(links(ItemFilter { require_image: true, strict_encoding: true, ..ItemFilter::from_opts() }), [
+        "https://www.example.com/2024/03/14/harbour-bridge",
+        "https://www.example.com/2024/03/13/cafe-ferries",
+        "https://www.example.com/2024/03/09/harbour-bridge-photos",
+    ]);
+    // --max-items-per-host keeps the newest
+    assert_eq!(links(ItemFilter { max_items_per_host: Some(1), ..ItemFilter::from_opts() }),
+        ["https://www.example.com/2024/03/14/harbour-bridge"]);
+    // --dedup-by title folds the second bridge story into the first
+    assert!(!links(ItemFilter { dedup_by: DedupKey::Title, ..ItemFilter::from_opts() })
+        .contains(&"https://www.example.com/2024/03/09/harbour-bridge-photos".to_string()));
+}
+
+#[test]
+fn huffpost_listing_crawl() {
+    init_opts();
+    let base = url("https://www.huffpost.com/news/");
+    let listing = Html::parse_document(&fixture("huffpost_listing.html"));
+    let fetcher = MapFetcher::new(&[
+        ("https://www.huffpost.com/entry/storm-closes-schools_n_65f1a2b3c4d5e6f7a8b90001", &fixture("huffpost_entry_1.html")),
+        ("https://www.huffpost.com/entry/museum-returns-artifacts_n_65f1a2b3c4d5e6f7a8b90002", &fixture("huffpost_entry_2.html")),
+    ]);
+    let items = extract_from_html(&fetcher, &listing, &base, 20);
+    assert_eq!(items, vec![
+        Item {
+            title: "Storm Closes Schools Across Three Counties".into(),
+            link: "https://www.huffpost.com/entry/storm-closes-schools_n_65f1a2b3c4d5e6f7a8b90001".into(),
+            description: Some("Officials cited flooding and downed power lines.".into()),
+            pub_date: Some("2024-03-15T06:00:00Z".into()),
+            updated: Some("2024-03-15T06:00:00Z".into()),
+            image: Some("https://img.huffingtonpost.com/storm.jpg".into()),
+            images: vec!["https://img.huffingtonpost.com/storm.jpg".into()],
+            ..Default::default()
+        },
+        Item {
+            title: "Museum Returns Looted Artifacts To Their Origin".into(),
+            link: "https://www.huffpost.com/entry/museum-returns-artifacts_n_65f1a2b3c4d5e6f7a8b90002".into(),
+            description: Some("The objects were taken during the colonial era.".into()),
+            pub_date: Some("2024-03-14T12:00:00Z".into()),
+            updated: Some("2024-03-14T12:00:00Z".into()),
+            image: Some("https://www.huffpost.com/images/artifacts.jpg".into()),
+            images: vec!["https://www.huffpost.com/images/artifacts.jpg".into()],
+            ..Default::default()
+        },
+    ]);
+}
+
+#[test]
+fn amp_article_meta_fallback() {
+    init_opts();
+    let base = url("https://news.example.org/");
+    let cand = url("https://news.example.org/amp/2024/03/10/rail-strike-ends");
+    let doc = Html::parse_document(&fixture("amp_article.html"));
+    let mut items = Vec::new();
+    extract_item_from_doc(&doc, &cand, &base, &mut items);
+    assert_eq!(items, vec![Item {
+        title: "Rail Strike Ends With Pay Deal".into(),
+        link: "https://news.example.org/amp/2024/03/10/rail-strike-ends".into(),
+        description: Some("Unions accepted a 6% raise over two years.".into()),
+        pub_date: Some("Sun, 10 Mar 2024 09:15:00 +0000".into()),
+        updated: Some("Sun, 10 Mar 2024 09:15:00 +0000".into()),
+        ..Default::default()
+    }]);
+}
+
+#[test]
+fn article_elements_skip_promos() {
+    init_opts();
+    let base = url("https://science.example.net/");
+    let doc = Html::parse_document(&fixture("article_elements.html"));
+    let mut items = Vec::new();
+    extract_article_elements(&doc, &base, 20, &mut items);
+    assert_eq!(items, vec![Item {
+        title: "Telescope Spots Water Vapour On Distant Planet".into(),
+        link: "https://science.example.net/2024/02/28/telescope-water-vapour".into(),
+        description: Some("Astronomers say the finding narrows the search for habitable worlds.".into()),
+        ..Default::default()
+    }]);
+}
+
+#[test]
+fn meta_refresh_interstitial_followed() {
+    init_opts();
+    let base = url("https://news.example.org/");
+    let inner = MapFetcher::new(&[
+        ("https://news.example.org/go/2024/rail-strike", &fixture("meta_refresh.html")),
+        ("https://news.example.org/amp/2024/03/10/rail-strike-ends", &fixture("amp_article.html")),
+    ]);
+    let fetcher = MetaRefreshFetcher { inner: &inner };
+    let mut items = Vec::new();
+    fetch_candidates(&fetcher, &[url("https://news.example.org/go/2024/rail-strike")], &base, 20, &mut items);
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0].title, "Rail Strike Ends With Pay Deal");
+    assert_eq!(items[0].link, "https://news.example.org/amp/2024/03/10/rail-strike-ends");
+}
+
+#[test]
+fn article_query_param_urls() {
+    init_opts();
+    let base = url("https://forum.example.com/");
+    let topic = url("https://forum.example.com/viewtopic.php?t=12345");
+    assert!(!is_listing_page(&topic, &base));
+    assert!(is_listing_page(&url("https://forum.example.com/viewforum.php?f=2"), &base));
+    assert_eq!(
+        canonicalize_url_str("https://forum.example.com/viewtopic.php?t=12345&utm_source=x#p9"),
+        "https://forum.example.com/viewtopic.php?t=12345",
+    );
+
+    let mut items = vec![
+        Item { title: "Printer driver crashes on resume".into(), link: topic.to_string(), ..Default::default() },
+        Item { title: "Kernel 6.8 regression thread".into(), link: "https://forum.example.com/viewtopic.php?t=12346".into(), ..Default::default() },
+        Item { title: "Printer driver crashes on resume".into(), link: "https://forum.example.com/viewtopic.php?t=12345&utm_medium=rss".into(), ..Default::default() },
+    ];
+    filter_items(&base, &mut items);
+    let links: Vec<&str> = items.iter().map(|it| it.link.as_str()).collect();
+    assert_eq!(links, ["https://forum.example.com/viewtopic.php?t=12345", "https://forum.example.com/viewtopic.php?t=12346"]);
+}
+
+#[test]
+fn linkless_items_get_content_guids() {
+    init_opts();
+    let base = url("https://science.example.net/2024/digest/weekly");
+    let doc = Html::parse_document(r#"<html><body>
+        <article><h2>Comet Returns After 70 Years</h2><p>Visible to the naked eye next week.</p></article>
+        <article><h2>Bee Populations Rebound</h2><p>Survey finds a third more hives.</p></article>
+        <article><h2>New Mineral Named</h2><p>Found in a Greenland core sample.</p></article>
+    </body></html>"#);
+    let mut items = Vec::new();
+    extract_article_elements(&doc, &base, 20, &mut items);
+    filter_items(&base, &mut items);
+    assert_eq!(items.len(), 3);
+    assert!(items.iter().all(|it| it.link == base.as_str()));
+
+    let guids: Vec<(String, bool)> = items.iter().map(|it| item_guid(it, &base)).collect();
+    assert!(guids.iter().all(|(g, permalink)| !permalink && g.starts_with("urn:html2rss:")));
+    let distinct: HashSet<&String> = guids.iter().map(|(g, _)| g).collect();
+    assert_eq!(distinct.len(), 3);
+}
+
+#[test]
+fn dedup_key_strategies() {
+    init_opts();
+    let base = url("https://www.example.com/");
+    let item = |title: &str, path: &str, desc: &str, guid: Option<&str>| Item {
+        title: title.into(),
+        link: format!("https://www.example.com/2024/05/{}", path),
+        description: Some(desc.into()),
+        guid: guid.map(String::from),
+        ..Default::default()
+    };
+    let items = vec![
+        item("Harbour Bridge Reopens", "01/bridge", "Traffic flows again.", None),
+        item("Harbour bridge reopens!", "01/bridge?utm_source=social", "Traffic flows again.", None),
+        item("Harbour Bridge Reopens", "02/bridge-update", "Tolls are waived for a week.", None),
+        item("Ferry Timetable Changes", "03/ferry", "Fewer crossings on Sundays.", Some("tag:example.com,2024:ferry")),
+        item("Ferry Timetable Changes (amended)", "04/ferry-amended", "Sunday crossings restored.", Some("tag:example.com,2024:ferry")),
+        item("Harbour Bridge Reopens", "05/bridge-repost", "Traffic flows again.", None),
+    ];
+    let kept = |by: DedupKey| {
+        let mut items = items.clone();
+        filter_items_with(&base, &mut items, ItemFilter { dedup_by: by, ..ItemFilter::from_opts() });
+        items.iter().map(|it| it.link.rsplit('/').next().unwrap().to_string()).collect::<Vec<_>>()
+    };
+    assert_eq!(kept(DedupKey::Link), ["bridge", "bridge-update", "ferry", "ferry-amended", "bridge-repost"]);
+    assert_eq!(kept(DedupKey::Guid), ["bridge", "bridge-update", "ferry", "bridge-repost"]);
+    assert_eq!(kept(DedupKey::Title), ["bridge", "ferry", "ferry-amended"]);
+    assert_eq!(kept(DedupKey::ContentHash), ["bridge", "bridge-update", "ferry", "ferry-amended"]);
+
+    // a republished update under the same title keeps the newest date, as dedupe_similar does
+    let dated = |path: &str, desc: &str, date: &str| Item { pub_date: Some(date.into()), ..item("Harbour Bridge Reopens", path, desc, None) };
+    let republished = vec![
+        dated("01/bridge", "Traffic flows again.", "2024-05-01T08:00:00Z"),
+        dated("06/bridge-latest", "Traffic flows again.", "2024-05-06T08:00:00Z"),
+        dated("03/bridge-older", "Traffic flows again.", "2024-05-03T08:00:00Z"),
+    ];
+    for by in [DedupKey::Title, DedupKey::ContentHash] {
+        let mut items = republished.clone();
+        filter_items_with(&base, &mut items, ItemFilter { dedup_by: by, ..ItemFilter::from_opts() });
+        assert_eq!(items.iter().map(|it| it.link.as_str()).collect::<Vec<_>>(), ["https://www.example.com/2024/05/06/bridge-latest"], "{:?}", by);
+    }
+}
+
+#[test]
+fn guid_survives_title_edits() {
+    init_opts();
+    let base = url("https://www.example.com/");
+    let edited = |it: &Item| Item { title: format!("UPDATED: {} (with video)", it.title), ..it.clone() };
+
+    // permalink: the canonical link
+    let linked = Item {
+        title: "Bridge Reopens".into(),
+        link: "https://www.example.com/2024/05/01/bridge?utm_source=rss".into(),
+        ..Default::default()
+    };
+    let guid = item_guid(&linked, &base);
+    assert_eq!(guid, ("https://www.example.com/2024/05/01/bridge".to_string(), true));
+    assert_eq!(item_guid(&edited(&linked), &base), guid);
+
+    // urn:html2rss: hash of the first stable field, for a link that isn't http(s)
+    let unlinked = Item {
+        title: "Ferry Times".into(),
+        link: "ftp://files.example.com/timetable".into(),
+        pub_date: Some("2024-05-02T06:00:00Z".into()),
+        ..Default::default()
+    };
+    let guid = item_guid(&unlinked, &base);
+    assert!(guid.0.starts_with("urn:html2rss:") && !guid.1, "{:?}", guid);
+    assert_eq!(item_guid(&edited(&unlinked), &base), guid);
+}
+
+#[test]
+fn candidates_ranked_by_score() {
+    init_opts();
+    let base = url("https://www.example.com/");
+    let doc = Html::parse_document(r#"<html><body>
+        <a href="/about/team/contact">Contact</a>
+        <div class="card"><a href="/gallery/2024/spring"><img src="t.jpg"></a></div>
+        <a href="/features/long-read/ocean" rel="nofollow">A very long headline about the deep ocean floor</a>
+        <a href="/features/long-read/forest">A very long headline about ancient forests of the north</a>
+        <a href="/2024/03/14/story/harbour-bridge">Bridge</a>
+    </body></html>"#);
+    let links: Vec<String> = build_candidate_list(&doc, &base, 20).iter().map(|u| u.path().to_string()).collect();
+    // gallery (image + card) and forest (long text) tie at 2 and keep document order
+    assert_eq!(links, ["/2024/03/14/story/harbour-bridge", "/gallery/2024/spring", "/features/long-read/forest"]);
+    // a tight budget keeps the strongest candidates
+    let top: Vec<String> = build_candidate_list(&doc, &base, 1).iter().map(|u| u.path().to_string()).collect();
+    assert_eq!(top, ["/2024/03/14/story/harbour-bridge"]);
+}
+
+#[test]
+fn path_prefix_limits_candidates() {
+    init_opts();
+    let base = url("https://www.example.com/");
+    let doc = Html::parse_document(r#"<html><body>
+        <a href="/world/2024/03/14/harbour-bridge">Harbour bridge reopens to traffic after two years</a>
+        <a href="/world/europe/2024/03/14/rail-strike">Rail strike ends after nine days of talks</a>
+        <a href="/worldcup/2024/03/14/final-preview">Final preview: what to expect on Sunday night</a>
+        <a href="/sport/2024/03/14/derby">Derby ends in a dramatic late equaliser at home</a>
+        <a href="/world">World</a>
+    </body></html>"#);
+    let paths = |prefix| build_candidate_list_in(&doc, &[], &base, 20, prefix).iter().map(|u| u.path().to_string()).collect::<Vec<_>>();
+    let all = paths(None);
+    assert!(all.contains(&"/sport/2024/03/14/derby".to_string()) && all.contains(&"/worldcup/2024/03/14/final-preview".to_string()));
+    let world = paths(Some("/world/"));
+    assert_eq!(world, ["/world/2024/03/14/harbour-bridge", "/world/europe/2024/03/14/rail-strike"]);
+    assert_eq!(paths(Some("/world")), world);
+
+    assert_eq!(path_prefix("world").as_deref(), Ok("/world"));
+    assert!(path_prefix("/").is_err());
+    assert!(path_has_prefix(&url("https://www.example.com/world"), "/world/"));
+    assert!(!path_has_prefix(&url("https://www.example.com/worldcup/x"), "/world"));
+}
+
+#[test]
+fn unwrapped_link_keeps_original() {
+    init_opts();
+    let base = url("https://aggregator.example.com/top/");
+    let node: JsonValue = serde_json::from_str(r#"{
+        "@type": "NewsArticle",
+        "headline": "Harbour Bridge Reopens",
+        "url": "/out?url=https%3A%2F%2Fwww.example.com%2F2024%2F03%2F14%2Fharbour-bridge-reopens"
+    }"#).unwrap();
+    let it = json_ld_to_item(&node, &base).unwrap();
+    assert_eq!(it.link, "https://www.example.com/2024/03/14/harbour-bridge-reopens");
+    assert_eq!(it.original_link.as_deref(), Some("https://aggregator.example.com/out?url=https%3A%2F%2Fwww.example.com%2F2024%2F03%2F14%2Fharbour-bridge-reopens"));
+}
+
+#[test]
+fn meta_refresh_records_requested_url() {
+    init_opts();
+    let base = url("https://news.example.org/");
+    let inner = MapFetcher::new(&[
+        ("https://news.example.org/go/2024/rail-strike", &fixture("meta_refresh.html")),
+        ("https://news.example.org/amp/2024/03/10/rail-strike-ends", &fixture("amp_article.html")),
+    ]);
+    let fetcher = MetaRefreshFetcher { inner: &inner };
+    let mut items = Vec::new();
+    fetch_candidates(&fetcher, &[url("https://news.example.org/go/2024/rail-strike")], &base, 20, &mut items);
+    assert_eq!(items[0].link, "https://news.example.org/amp/2024/03/10/rail-strike-ends");
+    assert_eq!(items[0].original_link.as_deref(), Some("https://news.example.org/go/2024/rail-strike"));
+}
+
+#[test]
+fn head_first_skips_without_downloading() {
+    init_opts();
+    let article = "https://news.example.org/amp/2024/03/10/rail-strike-ends";
+    let pdf = "https://news.example.org/files/2024/03/report.pdf";
+    let gone = "https://news.example.org/2024/03/09/removed-story";
+    let no_head = "https://news.example.org/2024/03/08/legacy-server";
+    let body = fixture("amp_article.html");
+    let fetcher = MapFetcher::new(&[(article, &body), (pdf, "%PDF-1.7"), (no_head, &body)])
+        .with_head(pdf, 200, Some("application/pdf"))
+        .with_head(no_head, 405, None);
+
+    assert!(head_allows(&fetcher, &url(article)));
+    assert!(!head_allows(&fetcher, &url(pdf)));
+    assert!(!head_allows(&fetcher, &url(gone)));
+    // HEAD not allowed: fall back to the GET
+    assert!(head_allows(&fetcher, &url(no_head)));
+    assert!(fetcher.log.borrow().iter().all(|l| l.starts_with("HEAD ")));
+}
+
+#[test]
+fn repair_feed_reads_malformed_rss() {
+    init_opts();
+    let base = url("https://gazette.example.com/feed.xml");
+    let items = parse_feed_lenient(&fixture("broken_feed.xml"), &base);
+    assert_eq!(items, vec![
+        Item {
+            title: "Ferries & Buses Resume Full Timetable".into(),
+            link: "https://gazette.example.com/2024/03/15/ferries-buses-resume".into(),
+            description: Some("Services return to normal after the strike .".into()),
+            pub_date: Some("Fri, 15 Mar 2024 07:00:00 +0000".into()),
+            updated: Some("Fri, 15 Mar 2024 07:00:00 +0000".into()),
+            image: Some("https://gazette.example.com/img/ferry.jpg".into()),
+            images: vec!["https://gazette.example.com/img/ferry.jpg".into()],
+            ..Default::default()
+        },
+        Item {
+            title: "Council Votes On Harbour Plan".into(),
+            link: "https://gazette.example.com/2024/03/14/harbour-plan-vote".into(),
+            description: Some("Members voted 9–4 in favour.".into()),
+            pub_date: Some("Thu, 14 Mar 2024 18:30:00 +0000".into()),
+            updated: Some("Thu, 14 Mar 2024 18:30:00 +0000".into()),
+            ..Default::default()
+        },
+        Item {
+            title: "Lighthouse Open Day Announced".into(),
+            link: "https://gazette.example.com/2024/03/13/lighthouse-open-day".into(),
+            description: Some("Tours every hour from 10am".into()),
+            ..Default::default()
+        },
+    ]);
+}
+
+#[test]
+fn page_language_from_declarations() {
+    init_opts();
+    let lang = |html: &str| page_language(&Html::parse_document(html));
+    assert_eq!(lang(&fixture("amp_article.html")).as_deref(), Some("en"));
+    assert_eq!(lang(r#"<html lang="fr-CA"><head><title>Grève</title></head></html>"#).as_deref(), Some("fr-CA"));
+    assert_eq!(lang(r#"<html><head><meta http-equiv="Content-Language" content="de, en"></head></html>"#).as_deref(), Some("de"));
+    assert_eq!(lang(r#"<html><head><meta property="og:locale" content="es_ES"></head></html>"#).as_deref(), Some("es-ES"));
+    assert_eq!(lang("<html><head><title>x</title></head></html>"), None);
+}
+
+#[test]
+fn item_json_key_order() {
+    init_opts();
+    let it = Item { title: "Zebra Crossing Repainted".into(), link: "https://www.example.com/2024/03/01/zebra".into(), ..Default::default() };
+    let json = item_json(&it, "https://www.example.com/2024/03/01/zebra");
+    let keys: Vec<&str> = json.as_object().unwrap().keys().map(String::as_str).collect();
+    assert_eq!(keys, ["title", "link", "guid", "description", "pub_date", "updated", "image", "images",
+        "original_link", "language", "author", "publisher"]);
+    assert!(json.to_string().starts_with(r#"{"title":"Zebra Crossing Repainted","link":"#));
+}
+
+#[test]
+fn bad_links_filtered() {
+    init_opts();
+    let base = url("https://science.example.net/2024/digest/weekly");
+    let item = |title: &str, link: &str| Item { title: title.into(), link: link.into(), ..Default::default() };
+    let mut items = vec![
+        item("Empty", ""),
+        item("Hash", "https://science.example.net/2024/digest/weekly#"),
+        item("Comments", "https://science.example.net/2024/digest/weekly#comments"),
+        item("Mail", "mailto:tips@science.example.net"),
+        item("Phone", "tel:+15551234567"),
+        item("Script", "javascript:void(0)"),
+        item("Relative", "#top"),
+        item("Fallback", "https://science.example.net/2024/digest/weekly"),
+        item("Real", "https://science.example.net/2024/02/28/telescope-water-vapour#main"),
+    ];
+    filter_items(&base, &mut items);
+    let titles: Vec<&str> = items.iter().map(|it| it.title.as_str()).collect();
+    assert_eq!(titles, ["Fallback", "Real"]);
+}
+
+#[test]
+fn json_ld_merged_with_og_tags() {
+    init_opts();
+    let base = url("https://alpine.example.com/");
+    let cand = url("https://alpine.example.com/2024/03/12/glacier-retreat");
+    let doc = Html::parse_document(&fixture("jsonld_og_merge.html"));
+    let mut items = Vec::new();
+    extract_item_from_doc(&doc, &cand, &base, &mut items);
+    assert_eq!(items, vec![Item {
+        // headline and date from JSON-LD, image and description from og:
+        title: "Glacier Retreat Speeds Up Across the Alps".into(),
+        link: "https://alpine.example.com/2024/03/12/glacier-retreat".into(),
+        description: Some("Measurements show the ice lost a record 6% of its volume.".into()),
+        pub_date: Some("2024-03-12T09:45:00Z".into()),
+        updated: Some("2024-03-12T09:45:00Z".into()),
+        image: Some("https://alpine.example.com/img/glacier-wide.jpg".into()),
+        images: vec!["https://alpine.example.com/img/glacier-wide.jpg".into()],
+        ..Default::default()
+    }]);
+}
+
+#[test]
+fn channel_metadata_from_publisher_and_footer() {
+    init_opts();
+    let base = url("https://www.example.com/world/");
+    let doc = Html::parse_document(&fixture("jsonld_graph.html"));
+    let channel = channel_metadata(&doc, &base, &[]);
+    assert_eq!(channel.publisher.as_deref(), Some("Example Times"));
+    assert_eq!(channel.title(&base), "Example Times");
+
+    let doc = Html::parse_document(r#"<html><body><main>News</main>
+        <footer><p>Privacy | Terms</p><p>Copyright © 2019–2024 Alpine Times Media Ltd. All rights reserved.</p></footer>
+    </body></html>"#);
+    let channel = channel_metadata(&doc, &base, &[]);
+    assert_eq!(channel, Channel { copyright: Some("Copyright © 2019–2024 Alpine Times Media Ltd".into()), ..Default::default() });
+    assert_eq!(channel.title(&base), "Feed for www.example.com");
+
+    let doc = Html::parse_document(r#"<script type="application/ld+json">
+        {"@type": "WebSite", "copyrightHolder": {"@type": "Organization", "name": "Harbour Gazette"}, "copyrightYear": 2024}
+    </script>"#);
+    assert_eq!(channel_metadata(&doc, &base, &[]).copyright.as_deref(), Some("© 2024 Harbour Gazette"));
+}
+
+#[test]
+fn channel_contact_elements() {
+    init_opts();
+    assert_eq!(contact_email(" news@example.com  (Jane  Doe) ").as_deref(), Ok("news@example.com (Jane Doe)"));
+    assert_eq!(contact_email("webmaster@example.com").as_deref(), Ok("webmaster@example.com"));
+    for bad in ["Jane Doe", "jane@localhost", "a@b.com Jane", "@example.com"] {
+        assert!(contact_email(bad).is_err(), "{}", bad);
+    }
+    assert!(Args::try_parse_from(["html2rss", "--web-master", "not-an-email", "https://example.com/"]).is_err());
+
+    let base = url("https://www.example.com/");
+    let doc = Html::parse_document(r#"<script type="application/ld+json">
+        {"@type": "NewsArticle", "author": [{"@type": "Person", "name": "No Email"},
+            {"@type": "Person", "name": "Ann Editor", "email": "mailto:ann@example.com"}]}
+    </script>"#);
+    let contact = json_ld_contact(&json_ld_nodes(&doc, &base));
+    assert_eq!(contact.as_deref(), Some("ann@example.com (Ann Editor)"));
+
+    let channel = Channel { managing_editor: contact, ..Default::default() };
+    let xml = String::from_utf8(rss_document(&base, &channel, &[]).unwrap()).unwrap();
+    assert!(xml.contains("<managingEditor>ann@example.com (Ann Editor)</managingEditor>"), "{}", xml);
+    assert!(!xml.contains("<webMaster>"));
+}
+
+#[test]
+fn oversized_json_ld_skipped() {
+    init_opts();
+    let base = url("https://www.example.com/world/");
+    let padding = "x".repeat(opts().max_json_ld_bytes);
+    let page = format!(r#"<html><head><script type="application/ld+json">
+        {{"@type": "NewsArticle", "headline": "Giant Block", "url": "/2024/03/01/giant", "articleBody": "{}"}}
+    </script></head></html>"#, padding);
+    assert!(extract_from_json_ld(&Html::parse_document(&page), &base).is_none());
+
+    let small = page.replace(&padding, "short");
+    let items = extract_from_json_ld(&Html::parse_document(&small), &base).expect("small block parsed");
+    assert_eq!(items[0].title, "Giant Block");
+}
+
+#[test]
+fn malformed_json_ld_repaired() {
+    init_opts();
+    let base = url("https://www.example.com/");
+    let headline = |text: &str| parse_json_ld_block(text, &base).and_then(|v| v["headline"].as_str().map(String::from));
+    for (name, text) in [
+        ("trailing commas", r#"{"@type": "NewsArticle", "headline": "Bridge Reopens", "keywords": ["a", "b",],}"#),
+        ("CDATA wrapper", r#"//<![CDATA[
+            {"@type": "NewsArticle", "headline": "Bridge Reopens"}
+        //]]>"#),
+        ("comment wrapper", r#"<!-- {"@type": "NewsArticle", "headline": "Bridge Reopens"} -->"#),
+        ("escaped quotes", r#"{&quot;@type&quot;: &quot;NewsArticle&quot;, &quot;headline&quot;: &quot;Bridge Reopens&quot;}"#),
+        ("raw newline", "{\"@type\": \"NewsArticle\", \"headline\": \"Bridge\nReopens\"}"),
+    ] {
+        let got = headline(text).unwrap_or_else(|| panic!("{} not repaired", name));
+        assert_eq!(got.split_whitespace().collect::<Vec<_>>(), ["Bridge", "Reopens"], "{}", name);
+    }
+    // beyond repair: skipped, not guessed at
+    assert_eq!(headline(r#"{"@type": "NewsArticle", "headline": "Bridge Reopens""#), None);
+
+    // and a repaired block yields its article
+    let doc = Html::parse_document(r#"<script type="application/ld+json">
+        {"@type": "NewsArticle", "headline": "Bridge Reopens", "url": "/2024/05/01/bridge",}
+    </script>"#);
+    assert_eq!(extract_from_json_ld(&doc, &base).unwrap()[0].link, "https://www.example.com/2024/05/01/bridge");
+}
+
+#[test]
+fn json_ld_node_cap() {
+    init_opts();
+    let base = url("https://www.example.com/");
+    let graph: Vec<JsonValue> = (0..MAX_JSON_LD_NODES + 250).map(|i| serde_json::json!({
+        "@type": "NewsArticle", "headline": format!("Story {}", i), "url": format!("/2024/05/01/story-{}", i),
+    })).collect();
+    let doc = Html::parse_document(&format!(r#"<script type="application/ld+json">{}</script>"#,
+        serde_json::json!({ "@context": "https://schema.org", "@graph": graph })));
+    let items = extract_from_json_ld(&doc, &base).unwrap();
+    assert_eq!(items.len(), MAX_JSON_LD_NODES);
+    assert_eq!(items.last().unwrap().title, format!("Story {}", MAX_JSON_LD_NODES - 1));
+    // the channel-metadata walk is capped the same way
+    assert_eq!(json_ld_nodes(&doc, &base).len(), MAX_JSON_LD_NODES + 1);
+}
+
+#[test]
+fn next_data_island_articles() {
+    init_opts();
+    let base = url("https://pixelpost.example/latest");
+    let doc = Html::parse_document(&fixture("next_data.html"));
+    assert_eq!(extract_from_data_islands(&doc, &base), vec![
+        Item {
+            title: "Chipmaker Unveils 2nm Roadmap".into(),
+            link: "https://pixelpost.example/tech/2024/03/15/chipmaker-2nm-roadmap".into(),
+            description: Some("The company expects volume production in 2026.".into()),
+            pub_date: Some("2024-03-15T11:00:00Z".into()),
+            updated: Some("2024-03-15T11:00:00Z".into()),
+            image: Some("https://cdn.pixelpost.example/img/wafer.jpg".into()),
+            images: vec!["https://cdn.pixelpost.example/img/wafer.jpg".into()],
+            ..Default::default()
+        },
+        Item {
+            title: "Open-Source Router Firmware Hits 1.0".into(),
+            link: "https://pixelpost.example/tech/2024/03/14/router-firmware-1-0".into(),
+            description: Some("Ten years in the making.".into()),
+            pub_date: Some("2024-03-14T09:30:00Z".into()),
+            updated: Some("2024-03-14T09:30:00Z".into()),
+            ..Default::default()
+        },
+    ]);
+}
+
+#[test]
+fn read_more_fragment_appended() {
+    init_opts();
+    let page = url("https://city.example.org/transport/2024/03/12/tram-extension");
+    let doc = Html::parse_document(&fixture("teaser.html"));
+    assert_eq!(read_more_url(&doc, &page).map(String::from).as_deref(), Some("https://city.example.org/api/articles/4711/body"));
+
+    let fetcher = MapFetcher::new(&[(
+        "https://city.example.org/api/articles/4711/body",
+        r#"{"id": 4711, "html": "<p>Construction starts next spring.</p><p>Six new stops are planned.</p>"}"#,
+    )]);
+    let mut items = Vec::new();
+    extract_item_from_doc(&doc, &page, &url("https://city.example.org/"), &mut items);
+    expand_read_more(&fetcher, &doc, &page, &mut items);
+    assert_eq!(items[0].description.as_deref(),
+        Some("The regional council backed the plan on Tuesday. Construction starts next spring. Six new stops are planned."));
+
+    // fragment endpoint down: the teaser stays
+    let mut items = Vec::new();
+    extract_item_from_doc(&doc, &page, &url("https://city.example.org/"), &mut items);
+    expand_read_more(&MapFetcher::new(&[]), &doc, &page, &mut items);
+    assert_eq!(items[0].description.as_deref(), Some("The regional council backed the plan on Tuesday."));
+}
+
+#[test]
+fn cdata_split_on_terminator() {
+    init_opts();
+    let mut w = Writer::new(Vec::new());
+    write_cdata_element(&mut w, "description", "Use a[b[0]]>c & \"quotes\" safely").unwrap();
+    let xml = String::from_utf8(w.into_inner()).unwrap();
+    assert_eq!(xml, r#"<description><![CDATA[Use a[b[0]]]]><![CDATA[>c & "quotes" safely]]></description>"#);
+
+    // a reader joins the sections back into the original text
+    let mut reader = quick_xml::Reader::from_str(&xml);
+    let mut text = String::new();
+    loop {
+        match reader.read_event().unwrap() {
+            Event::CData(c) => text.push_str(&String::from_utf8_lossy(&c)),
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    assert_eq!(text, r#"Use a[b[0]]>c & "quotes" safely"#);
+}
+
+#[test]
+fn hostile_item_text_still_serializes() {
+    init_opts();
+    let base = url("https://www.example.com/");
+    // control characters, XML-forbidden noncharacters, a stray CDATA terminator and a
+    // description whose length cap lands inside a multi-byte character
+    let bad = Item {
+        title: "Bell\u{7} \u{0}rings \u{FFFE}twice\u{FFFF} ]]> &amp; <b>".into(),
+        link: "https://www.example.com/2024/05/01/bell".into(),
+        description: Some(format!("x{}", "é".repeat(3000))),
+        ..Default::default()
+    };
+    let good = Item { title: "Ferry Times".into(), link: "https://www.example.com/2024/05/02/ferry".into(), ..Default::default() };
+    let xml = String::from_utf8(rss_document(&base, &Channel::default(), &[bad, good]).unwrap()).unwrap();
+
+    let mut reader = quick_xml::Reader::from_str(&xml);
+    let (mut items, mut titles) = (0, Vec::new());
+    let mut in_title = false;
+    loop {
+        match reader.read_event().unwrap() {
+            Event::Start(e) if e.name().as_ref() == b"item" => items += 1,
+            Event::Start(e) => in_title = items > 0 && e.name().as_ref() == b"title",
+            Event::Text(t) if in_title => titles.push(t.unescape().unwrap().into_owned()),
+            Event::End(_) => in_title = false,
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    assert_eq!(items, 2);
+    assert_eq!(titles, ["Bell rings twice ]]> & <b>", "Ferry Times"]);
+}
+
+#[test]
+fn enclosure_length_from_head_probe() {
+    init_opts();
+    let base = url("https://www.example.com/");
+    let fetcher = MapFetcher::new(&[])
+        .with_head("https://cdn.example.com/img/bridge.jpg", 200, Some("image/jpeg"))
+        .with_length("https://cdn.example.com/img/bridge.jpg", 48213)
+        .with_head("https://cdn.example.com/img/gone.jpg", 404, None);
+    let item = |path: &str, image: &str, length: Option<u64>| Item {
+        title: path.into(),
+        link: format!("https://www.example.com/2024/05/01/{}", path),
+        image: Some(image.into()),
+        image_length: length,
+        ..Default::default()
+    };
+    let mut items = vec![
+        item("bridge", "https://cdn.example.com/img/bridge.jpg", None),
+        item("ferry", "https://cdn.example.com/img/ferry.jpg", Some(1200)),
+        item("gone", "https://cdn.example.com/img/gone.jpg", None),
+    ];
+    probe_enclosures(&fetcher, &mut items);
+    assert_eq!(items.iter().map(|it| it.image_length).collect::<Vec<_>>(), [Some(48213), Some(1200), None]);
+    // a length already known (JSON-LD contentSize) isn't probed again
+    assert_eq!(*fetcher.log.borrow(), ["HEAD https://cdn.example.com/img/bridge.jpg", "HEAD https://cdn.example.com/img/gone.jpg"]);
+
+    let mut w = Writer::new(Vec::new());
+    write_item(&mut w, &items[0], &base).unwrap();
+    let xml = String::from_utf8(w.into_inner()).unwrap();
+    assert!(xml.contains(r#"<enclosure url="https://cdn.example.com/img/bridge.jpg" length="48213"/>"#), "{}", xml);
+}
+
+#[test]
+fn pub_date_and_dc_date_agree() {
+    init_opts();
+    for raw in ["2024-03-14T08:30:00Z", "2024-03-13T17:05:00+01:00", "Wed, 13 Mar 2024 17:05:00 +0100", "March 12, 2024"] {
+        let (pub_date, dc_date) = item_date_values(raw);
+        let dc_date = dc_date.unwrap_or_else(|| panic!("no dc:date for {}", raw));
+        assert_eq!(DateTime::parse_from_rfc2822(&pub_date).unwrap(), DateTime::parse_from_rfc3339(&dc_date).unwrap(), "{}", raw);
+    }
+    assert_eq!(item_date_values("2024-03-13T17:05:00+01:00"),
+        ("Wed, 13 Mar 2024 17:05:00 +0100".to_string(), Some("2024-03-13T17:05:00+01:00".to_string())));
+    // unparseable: pubDate keeps the raw text, dc:date is left out rather than guessed
+    assert_eq!(item_date_values("last Tuesday"), ("last Tuesday".to_string(), None));
+}
+
+#[test]
+fn modified_dates_from_meta_and_json_ld() {
+    init_opts();
+    let base = url("https://www.example.com/");
+    let cand = url("https://www.example.com/2024/03/14/harbour-bridge");
+    let extract = |html: &str| {
+        let mut items = Vec::new();
+        extract_item_from_doc(&Html::parse_document(html), &cand, &base, &mut items);
+        let it = items.pop().unwrap();
+        (it.pub_date, it.updated)
+    };
+    let published = Some("2024-03-14T08:30:00Z".to_string());
+
+    // meta tags: article:modified_time, else og:updated_time
+    assert_eq!(extract(r#"<title>Bridge</title>
+        <meta property="article:published_time" content="2024-03-14T08:30:00Z">
+        <meta property="article:modified_time" content="2024-03-15T11:00:00Z">"#),
+        (published.clone(), Some("2024-03-15T11:00:00Z".to_string())));
+    assert_eq!(extract(r#"<title>Bridge</title>
+        <meta property="article:published_time" content="2024-03-14T08:30:00Z">
+        <meta property="og:updated_time" content="2024-03-16T09:00:00Z">"#),
+        (published.clone(), Some("2024-03-16T09:00:00Z".to_string())));
+
+    // JSON-LD dateModified
+    assert_eq!(extract(r#"<script type="application/ld+json">{"@type": "NewsArticle", "headline": "Bridge",
+        "datePublished": "2024-03-14T08:30:00Z", "dateModified": "2024-03-15T18:45:00Z"}</script>"#),
+        (published.clone(), Some("2024-03-15T18:45:00Z".to_string())));
+
+    // never modified: updated falls back to the publication date
+    assert_eq!(extract(r#"<title>Bridge</title><meta property="article:published_time" content="2024-03-14T08:30:00Z">"#),
+        (published.clone(), published));
+}
+
+#[test]
+fn noscript_only_links_become_candidates() {
+    init_opts();
+    let base = url("https://www.example.com/");
+    // a client-rendered listing: the article links exist only in the no-JS fallback
+    let doc = Html::parse_document(r#"<html><body>
+        <div id="app" data-src="/api/latest"></div>
+        <noscript>
+          <ul>
+            <li><a href="/2024/05/01/harbour-bridge-reopens">Harbour bridge reopens after two-year restoration</a></li>
+            <li><a href="/2024/05/02/ferry-timetable-changes">Ferry timetable changes for the summer season</a></li>
+          </ul>
+          <img src="/img/bridge.jpg">
+        </noscript>
+    </body></html>"#);
+    assert!(build_candidate_list_in(&doc, &[], &base, 20, None).is_empty());
+
+    let noscript = parse_noscript_blocks(&doc);
+    assert_eq!(noscript.len(), 1);
+    let paths: Vec<String> = build_candidate_list_in(&doc, &noscript, &base, 20, None).iter().map(|u| u.path().to_string()).collect();
+    assert_eq!(paths, ["/2024/05/01/harbour-bridge-reopens", "/2024/05/02/ferry-timetable-changes"]);
+}
+
+#[test]
+fn rss10_rdf_structure() {
+    init_opts();
+    let base = url("https://www.example.com/");
+    let items: Vec<Item> = ["2024/05/01/bridge", "2024/05/02/ferry"].iter().map(|p| Item {
+        title: p.to_string(),
+        link: format!("https://www.example.com/{}", p),
+        pub_date: Some("2024-05-01T08:00:00Z".into()),
+        ..Default::default()
+    }).collect();
+    let xml = String::from_utf8(rss10_document(&base, &Channel::default(), &items).unwrap()).unwrap();
+
+    let mut reader = quick_xml::Reader::from_str(&xml);
+    let mut path: Vec<String> = Vec::new();
+    let (mut root, mut seq, mut abouts, mut links) = (None, Vec::new(), Vec::new(), Vec::new());
+    let attr = |e: &BytesStart, name: &str| e.try_get_attribute(name).unwrap().map(|a| a.unescape_value().unwrap().into_owned());
+    loop {
+        match reader.read_event().unwrap() {
+            Event::Start(e) => {
+                let name = String::from_utf8(e.name().as_ref().to_vec()).unwrap();
+                if root.is_none() { root = Some(name.clone()); }
+                if path == ["rdf:RDF"] && name == "item" { abouts.push(attr(&e, "rdf:about").unwrap()); }
+                path.push(name);
+            }
+            Event::Empty(e) if e.name().as_ref() == b"rdf:li" => {
+                assert_eq!(path, ["rdf:RDF", "channel", "items", "rdf:Seq"]);
+                seq.push(attr(&e, "rdf:resource").unwrap());
+            }
+            Event::Text(t) if path == ["rdf:RDF", "item", "link"] => links.push(t.unescape().unwrap().into_owned()),
+            Event::End(_) => { path.pop(); }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    assert_eq!(root.as_deref(), Some("rdf:RDF"));
+    let expected: Vec<&str> = items.iter().map(|it| it.link.as_str()).collect();
+    assert_eq!(seq, expected);
+    assert_eq!(abouts, expected);
+    assert_eq!(links, expected);
+}
+
+#[test]
+fn summaries_trim_at_boundaries() {
+    use SummaryUnit::*;
+    // words: never mid-word, dangling punctuation dropped
+    assert_eq!(summarize("The quick brown fox jumps over the lazy dog", 4, Words), "The quick brown fox…");
+    assert_eq!(summarize("Rain, wind, and snow are expected tonight", 2, Words), "Rain, wind…");
+    assert_eq!(summarize("Ünïcödé wörds ärë fïnë hërë", 3, Words), "Ünïcödé wörds ärë…");
+    assert_eq!(summarize("  Short enough  ", 4, Words), "Short enough");
+    // sentences: whole sentences, including full-width CJK punctuation
+    assert_eq!(summarize("Polls closed at 10pm. Counting starts at midnight! Results by dawn?", 2, Sentences),
+        "Polls closed at 10pm. Counting starts at midnight! …");
+    assert_eq!(summarize("東京で会議が開かれた。結果は明日発表される。", 1, Sentences), "東京で会議が開かれた。 …");
+    assert_eq!(summarize("One sentence only.", 1, Sentences), "One sentence only.");
+    // characters: counted as chars, not bytes, and backed off to a word boundary
+    assert_eq!(summarize("Café crème brûlée à la carte", 12, Chars), "Café crème…");
+    assert_eq!(summarize("日本語のテキストを切る", 5, Chars), "日本語のテ…");
+}
+
+#[test]
+fn unicode_forms_differ_on_compatibility_characters() {
+    let form = |s: &str, f| normalize_unicode(s.to_string(), f);
+    // ligatures, full-width letters and digits, circled numbers
+    let text = "\u{FB01}nal o\u{FB00}er: \u{FF21}\u{FF22}\u{FF23} \u{FF11}\u{FF12}% \u{2460}";
+    assert_eq!(form(text, UnicodeForm::Nfkc), "final offer: ABC 12% 1");
+    assert_eq!(form(text, UnicodeForm::Nfc), text);
+    assert_eq!(form(text, UnicodeForm::None), text);
+    // both compose a decomposed accent; None leaves it as sent
+    let decomposed = "Cafe\u{301}";
+    assert_eq!(form(decomposed, UnicodeForm::Nfc), "Caf\u{E9}");
+    assert_eq!(form(decomposed, UnicodeForm::Nfkc), "Caf\u{E9}");
+    assert_eq!(form(decomposed, UnicodeForm::None), decomposed);
+}
+
+#[test]
+fn time_element_dates() {
+    init_opts();
+    let date = |html: &str| time_element_date(&Html::parse_document(html));
+    assert_eq!(date(r#"<p class="byline">By Ana <time datetime="2024-03-15T10:20:00+01:00">15 March</time></p>"#).as_deref(),
+        Some("2024-03-15T10:20:00+01:00"));
+    assert_eq!(date("<p>Published <time>March 15, 2024</time></p>").as_deref(), Some("2024-03-15T00:00:00+00:00"));
+    // an unusable attribute falls back to the text
+    assert_eq!(date(r#"<time datetime="yesterday">2024-03-14 18:05</time>"#).as_deref(), Some("2024-03-14T18:05:00+00:00"));
+    assert_eq!(date("<time>Updated recently</time>"), None);
+
+    let page = url("https://science.example.net/2024/03/15/tidal-energy");
+    let doc = Html::parse_document(r#"<html><head><title>Tidal Turbines Power a Town</title></head>
+        <body><h1>Tidal Turbines Power a Town</h1><time datetime="2024-03-15">Mar 15</time></body></html>"#);
+    let mut items = Vec::new();
+    extract_item_from_doc(&doc, &page, &url("https://science.example.net/"), &mut items);
+    assert_eq!(items[0].pub_date.as_deref(), Some("2024-03-15"));
+}
+
+#[test]
+fn news_sitemap_items_without_fetching() {
+    init_opts();
+    let base = url("https://news.example.org/");
+    let fetcher = MapFetcher::new(&[
+        ("https://news.example.org/robots.txt", "User-agent: *\nSitemap: https://news.example.org/sitemaps/news.xml\n"),
+        ("https://news.example.org/sitemaps/news.xml", &fixture("sitemap_news.xml")),
+    ]);
+    let items = items_from_news_sitemap(&fetcher, &base, 20);
+    assert_eq!(items, vec![
+        Item {
+            title: "Budget Passes After Late-Night Vote".into(),
+            link: "https://news.example.org/2024/03/15/budget-vote".into(),
+            pub_date: Some("2024-03-15T16:40:00+00:00".into()),
+            updated: Some("2024-03-15T16:40:00+00:00".into()),
+            ..Default::default()
+        },
+        Item {
+            title: "River Levels Fall & Roads Reopen".into(),
+            link: "https://news.example.org/2024/03/15/river-levels".into(),
+            pub_date: Some("2024-03-15T09:10:00+00:00".into()),
+            updated: Some("2024-03-15T09:10:00+00:00".into()),
+            ..Default::default()
+        },
+    ]);
+    // only robots.txt and the sitemap were requested, no article pages
+    assert_eq!(fetcher.log.borrow().len(), 2);
+}
+
+#[test]
+fn plain_sitemap_falls_back_to_fetching() {
+    init_opts();
+    let base = url("https://news.example.org/");
+    let fetcher = MapFetcher::new(&[
+        ("https://news.example.org/sitemap-news.xml", r#"<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+            <url><loc>https://news.example.org/about/</loc><lastmod>2023-01-01</lastmod></url>
+            <url><loc>https://news.example.org/amp/2024/03/10/rail-strike-ends</loc><lastmod>2024-03-10</lastmod></url>
+        </urlset>"#),
+        ("https://news.example.org/amp/2024/03/10/rail-strike-ends", &fixture("amp_article.html")),
+    ]);
+    let items = items_from_news_sitemap(&fetcher, &base, 20);
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0].title, "Rail Strike Ends With Pay Deal");
+}
+
+#[test]
+fn same_title_keeps_newest_date() {
+    init_opts();
+    let item = |link: &str, date: &str, desc: &str| Item {
+        title: "Election Results: Live Count".into(),
+        link: link.into(),
+        description: Some(desc.into()),
+        pub_date: Some(date.into()),
+        ..Default::default()
+    };
+    let mut items = vec![
+        item("https://www.example.com/2024/03/10/results", "2024-03-10T22:00:00Z", "Early returns from the northern districts are in, with turnout near 60 percent."),
+        Item { title: "Ferry Timetable Changes".into(), link: "https://www.example.com/2024/03/11/ferry".into(), ..Default::default() },
+        item("https://www.example.com/2024/03/11/results", "2024-03-11T06:30:00Z", "Final tally."),
+    ];
+    dedupe_similar(&mut items, 0.8);
+    let links: Vec<&str> = items.iter().map(|it| it.link.as_str()).collect();
+    // the update wins despite its shorter description, and keeps the original's position
+    assert_eq!(links, ["https://www.example.com/2024/03/11/results", "https://www.example.com/2024/03/11/ferry"]);
+}
+
+#[test]
+fn html_index_escapes_values() {
+    init_opts();
+    let base = url("https://www.example.com/");
+    let items = vec![Item {
+        title: "Q&A: <script>alert(1)</script>".into(),
+        link: "https://www.example.com/2024/03/01/qa?a=1&b=\"2\"".into(),
+        description: Some("Tom & Jerry's \"best\" <b>bits</b>".into()),
+        pub_date: Some("2024-03-01T12:00:00Z".into()),
+        ..Default::default()
+    }];
+    let page = html_index(&base, &Channel::default(), &items);
+    assert!(page.starts_with("<!DOCTYPE html>"));
+    assert!(page.contains("<title>Feed for www.example.com</title>"));
+    assert!(page.contains(r#"<a href="https://www.example.com/2024/03/01/qa?a=1&amp;b=&quot;2&quot;">Q&amp;A: &lt;script&gt;alert(1)&lt;/script&gt;</a>"#));
+    assert!(page.contains(r#"<time datetime="2024-03-01T12:00:00+00:00">2024-03-01 12:00</time>"#));
+    assert!(page.contains("<p>Tom &amp; Jerry's \"best\" &lt;b&gt;bits&lt;/b&gt;</p>"));
+    assert!(!page.contains("<script>"));
+}
+
+#[test]
+fn realistic_headers_match_user_agent() {
+    init_opts();
+    for ua in USER_AGENTS {
+        let headers = browser_headers(ua);
+        let get = |name: &str| headers.iter().find(|(n, _)| n.as_str() == name).map(|(_, v)| v.as_str());
+        let accept = get("accept").unwrap();
+        assert!(get("accept-language").is_some_and(|l| l.starts_with("en")));
+        assert_eq!(get("sec-fetch-mode"), Some("navigate"));
+        match ua_family(ua) {
+            BrowserFamily::Chrome => {
+                assert!(accept.contains("image/apng") && accept.contains("signed-exchange"), "{}", ua);
+                let major = ua.split("Chrome/").nth(1).unwrap().split('.').next().unwrap();
+                assert!(get("sec-ch-ua").unwrap().contains(&format!(r#"v="{}""#, major)));
+            }
+            BrowserFamily::Firefox => {
+                assert!(accept.contains("image/webp") && !accept.contains("apng"), "{}", ua);
+                assert_eq!(get("sec-ch-ua"), None);
+            }
+            BrowserFamily::Safari => {
+                assert!(!accept.contains("image/"), "{}", ua);
+                assert_eq!(get("sec-ch-ua"), None);
+            }
+        }
+    }
+    assert_eq!(ua_family(USER_AGENTS[3]), BrowserFamily::Firefox);
+    assert_eq!(ua_family(USER_AGENTS[4]), BrowserFamily::Safari);
+}
+
+#[test]
+fn podcast_episode_json_ld() {
+    init_opts();
+    let base = url("https://radio.example.com/shows/night-sky/");
+    let doc = Html::parse_document(&fixture("podcast_episode.html"));
+    let items = extract_from_json_ld(&doc, &base).expect("episode item");
+    let it = &items[0];
+    assert_eq!(it.title, "Episode 42: Meteor Showers");
+    assert_eq!(it.audio.as_deref(), Some("https://cdn.radio.example.com/night-sky/042.mp3"));
+    assert_eq!(it.audio_type.as_deref(), Some("audio/mpeg"));
+    assert_eq!(it.audio_length, Some(24_117_248));
+    assert_eq!(it.duration.as_deref(), Some("PT41M7S"));
+    assert_eq!(it.episode, Some(42));
+    assert_eq!(channel_metadata(&doc, &base, &items).image.as_deref(), Some("https://radio.example.com/img/night-sky-cover.jpg"));
+
+    let mut w = Writer::new(Vec::new());
+    write_itunes_item_tags(&mut w, it).unwrap();
+    assert_eq!(String::from_utf8(w.into_inner()).unwrap(), concat!(
+        "<itunes:author>Dana Ortiz</itunes:author>",
+        "<itunes:summary>When to look up, and where.</itunes:summary>",
+        r#"<itunes:image href="https://radio.example.com/img/ep42.jpg"/>"#,
+        "<itunes:duration>00:41:07</itunes:duration>",
+        "<itunes:episode>42</itunes:episode>",
+    ));
+
+    assert_eq!(itunes_duration("PT1H2M3S").as_deref(), Some("01:02:03"));
+    assert_eq!(itunes_duration("3600").as_deref(), Some("01:00:00"));
+    assert_eq!(itunes_duration("42:10").as_deref(), Some("42:10"));
+    assert_eq!(itunes_duration("about an hour"), None);
+}
+
+#[test]
+fn url_fetched_once_across_stages() {
+    init_opts();
+    let base = url("https://news.example.org/");
+    let article = "https://news.example.org/world/europe/rail-strike-ends";
+    // reachable both from the section page (listing stage) and directly (candidate stage)
+    let start = Html::parse_document(r#"<html><body>
+        <a href="/section/world/"><img src="/w.jpg">World news: the latest from Europe and beyond</a>
+        <div class="card"><a href="/world/europe/rail-strike-ends">More</a></div>
+    </body></html>"#);
+    let section = r#"<html><body><a href="/world/europe/rail-strike-ends"><img src="/r.jpg">Rail strike</a></body></html>"#;
+    let page = r#"<html><head><meta property="og:title" content="Rail Strike Ends After Nine Days">
+        <meta property="og:description" content="Trains run again on Monday."></head><body></body></html>"#;
+    let inner = MapFetcher::new(&[("https://news.example.org/section/world/", section), (article, page)]);
+    let fetcher = VisitedFetcher::new(&inner);
+    let items = extract_from_html(&fetcher, &start, &base, 10);
+
+    assert_eq!(items.iter().map(|it| it.title.as_str()).collect::<Vec<_>>(), vec!["Rail Strike Ends After Nine Days"]);
+    assert_eq!(inner.log.borrow().iter().filter(|l| *l == &format!("GET {}", article)).count(), 1);
+    // a tracking-param variant of the same page is the same URL
+    let again = fetcher.get(&url(&format!("{}?utm_source=rss", article)));
+    assert!(matches!(again.err().and_then(|e| e.downcast::<FetchError>().ok()).as_deref(), Some(FetchError::AlreadyFetched(_))));
+}
+
+#[test]
+fn windows_1252_output_round_trip() {
+    let enc = output_charset("Windows-1252").unwrap();
+    assert!(output_charset("utf-16le").is_err());
+    assert!(output_charset("klingon").is_err());
+
+    let mut w = Writer::new(Vec::new());
+    w.write_event(Event::Decl(xml_decl(enc))).unwrap();
+    write_text_element(&mut w, "title", "Café déjà vu – 5 € à Zürich (東京)").unwrap();
+    let bytes = transcode_xml(w.into_inner(), enc);
+
+    assert!(bytes.starts_with(br#"<?xml version="1.0" encoding="windows-1252"?>"#));
+    assert!(bytes.windows(4).any(|b| b == b"Caf\xe9"));
+    let (text, _, had_errors) = encoding_rs::WINDOWS_1252.decode(&bytes);
+    assert!(!had_errors);
+    assert!(text.ends_with("<title>Café déjà vu – 5 € à Zürich (&#26481;&#20140;)</title>"), "{}", text);
+
+    // default output is left as UTF-8
+    assert_eq!(transcode_xml("é".as_bytes().to_vec(), UTF_8), "é".as_bytes());
+}
+
+#[test]
+fn cdata_and_comments_survive_transcoding() {
+    let enc = output_charset("Windows-1252").unwrap();
+    let original = "Café in 東京 &#1; ✓";
+    let mut w = Writer::new(Vec::new());
+    w.write_event(Event::Decl(xml_decl(enc))).unwrap();
+    w.write_event(Event::Comment(BytesText::from_escaped(" warning: 東京 café "))).unwrap();
+    write_cdata_element(&mut w, "description", original).unwrap();
+    let bytes = transcode_xml(w.into_inner(), enc);
+    let (xml, _, had_errors) = encoding_rs::WINDOWS_1252.decode(&bytes);
+    assert!(!had_errors);
+
+    // the literal "&#1;" in the CDATA stays literal text; 東京 and ✓ are references outside it
+    let mut reader = quick_xml::Reader::from_str(&xml);
+    let (mut text, mut comment) = (String::new(), String::new());
+    loop {
+        match reader.read_event().unwrap() {
+            Event::CData(c) => text.push_str(&String::from_utf8_lossy(&c)),
+            Event::Text(t) => text.push_str(&t.unescape().unwrap()),
+            Event::Comment(c) => comment.push_str(&String::from_utf8_lossy(&c)),
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    assert_eq!(text, original);
+    assert_eq!(comment, " warning: ?? café ");
+}
+
+#[test]
+fn word_count_from_json_ld_and_text() {
+    init_opts();
+    let base = url("https://www.example.com/");
+    let doc = Html::parse_document(r#"<html><head><script type="application/ld+json">
+        {"@type": "NewsArticle", "headline": "Council Approves New Budget", "url": "/2024/05/02/budget", "wordCount": "1,150"}
+        </script></head><body></body></html>"#);
+    let items = extract_from_json_ld(&doc, &base).expect("JSON-LD item");
+    assert_eq!(items[0].word_count, Some(1150));
+    assert_eq!(reading_time_note(1150), "1150 words, 5 min read");
+    assert_eq!(reading_time_note(40), "40 words, 1 min read");
+
+    // article paragraphs only; navigation and footer text is not counted
+    let page = Html::parse_document(r#"<html><body><nav><p>Home News Sport</p></nav>
+        <article><h1>Headline</h1><p>The quick brown fox jumps.</p><p>Over the <em>lazy</em> dog.</p></article>
+        <footer><p>All rights reserved</p></footer></body></html>"#);
+    assert_eq!(page_word_count(&page), Some(9));
+    assert_eq!(page_word_count(&Html::parse_document("<html><body><p>Just this</p></body></html>")), Some(2));
+    assert_eq!(page_word_count(&Html::parse_document("<html><body></body></html>")), None);
+}
+
+#[test]
+fn title_and_description_templates() {
+    let it = Item {
+        title: "Harbour Bridge Reopens".into(),
+        link: "https://www.example.com/2024/03/14/bridge".into(),
+        description: Some("Traffic is back.".into()),
+        pub_date: Some("Thu, 14 Mar 2024 08:30:00 +0000".into()),
+        ..Default::default()
+    };
+    let vars = |name: &str| match name {
+        "title" => Some(it.title.clone()),
+        "date" => Some("2024-03-14".to_string()),
+        "host" => Some("example.com".to_string()),
+        _ => None,
+    };
+    assert_eq!(expand_template("[{host}] {title}", vars).unwrap(), "[example.com] Harbour Bridge Reopens");
+    assert_eq!(expand_template("{date} – {title} {{live}}", vars).unwrap(), "2024-03-14 – Harbour Bridge Reopens {live}");
+
+    assert!(item_template("{site}: {title}").is_ok());
+    assert!(item_template("{headline}").unwrap_err().contains("unknown placeholder {headline}"));
+    assert!(item_template("{title").is_err());
+    assert!(item_template("title}").is_err());
+
+    // markup in a value stays text and is escaped by the writer
+    let vars = |name: &str| (name == "title").then(|| "Q&A: <Live>".to_string());
+    let mut w = Writer::new(Vec::new());
+    write_text_element(&mut w, "title", &expand_template("Site | {title}", vars).unwrap()).unwrap();
+    assert_eq!(String::from_utf8(w.into_inner()).unwrap(), "<title>Site | Q&amp;A: &lt;Live&gt;</title>");
+}
+
+#[test]
+fn start_url_serving_a_feed() {
+    init_opts();
+    let start = "https://gazette.example.net/feed";
+    let fetcher = MapFetcher::new(&[(start, &fixture("start_feed.xml"))]);
+    // served without a feed content type, so the root element decides
+    let page = fetcher.get(&url(start)).unwrap();
+    assert!(page.is_feed());
+
+    let items = parse_feed_lenient(&page.body, &page.url);
+    assert_eq!(items.iter().map(|it| it.title.as_str()).collect::<Vec<_>>(),
+        vec!["Library Extends Weekend Hours", "Bridge Repairs Finish Early"]);
+    assert_eq!(items[0].link, "https://gazette.example.net/2024/04/02/library-hours");
+
+    // read into our model, the entries go through the same filters as crawled items
+    let read = |filter: ItemFilter| feed_source_items(&page.body, &page.url, &page.url, filter);
+    assert_eq!(read(ItemFilter::from_opts()).len(), 2);
+    assert!(read(ItemFilter { require_image: true, ..ItemFilter::from_opts() }).is_empty());
+    assert_eq!(read(ItemFilter { max_items_per_host: Some(1), ..ItemFilter::from_opts() }).len(), 1);
+
+    let page_with = |body: &str, ct: Option<&str>| Page { url: url(start), body: body.into(), content_type: ct.map(str::to_string) };
+    assert!(page_with("", Some("application/atom+xml; charset=utf-8")).is_feed());
+    assert!(page_with("\u{feff}<feed xmlns=\"http://www.w3.org/2005/Atom\">", Some("text/xml")).is_feed());
+    assert!(!page_with("<!DOCTYPE html><html><body><rss>", Some("text/html")).is_feed());
+    assert!(!page_with("<?xml version=\"1.0\"?><urlset>", Some("application/xml")).is_feed());
+}
+
+#[test]
+fn amp_cache_urls_unwrapped() {
+    let base = url("https://www.example.com/news/");
+    let cases = [
+        ("https://www-example-com.cdn.ampproject.org/i/s/www.example.com/img/lead.jpg", "https://www.example.com/img/lead.jpg"),
+        ("https://www-example-com.cdn.ampproject.org/ii/w820/s/www.example.com/img/lead.jpg", "https://www.example.com/img/lead.jpg"),
+        ("https://example-org.cdn.ampproject.org/i/example.org/photo.png", "http://example.org/photo.png"),
+        ("https://www-example-com.cdn.ampproject.org/c/s/www.example.com/2024/03/10/story.amp.html", "https://www.example.com/2024/03/10/story.amp.html"),
+        ("https://www-example-com.cdn.ampproject.org/v/s/www.example.com/story?id=7&amp_js_v=0.1&usqp=mq331AQ", "https://www.example.com/story?id=7"),
+        ("https://www.google.com/amp/s/www.example.com/2024/03/10/story.amp", "https://www.example.com/2024/03/10/story.amp"),
+    ];
+    for (amp, origin) in cases {
+        assert_eq!(normalize_maybe_url(&base, amp).as_deref(), Some(origin), "{}", amp);
+    }
+    // ordinary URLs, and cache URLs without an origin path, are left alone
+    assert_eq!(normalize_maybe_url(&base, "/img/a.jpg").as_deref(), Some("https://www.example.com/img/a.jpg"));
+    assert_eq!(amp_cache_origin(&url("https://cdn.ampproject.org/v0.js")), None);
+    assert_eq!(amp_cache_origin(&url("https://www.google.com/search?q=amp")), None);
+}
+
+#[test]
+fn quiet_and_verbose_are_exclusive() {
+    assert!(Args::try_parse_from(["html2rss", "-q", "https://example.com/"]).unwrap().quiet);
+    assert!(Args::try_parse_from(["html2rss", "-q", "-v", "https://example.com/"]).is_err());
+}
+
+#[test]
+fn text_density_tells_articles_from_listings() {
+    let article = Html::parse_document(&fixture("plain_article.html"));
+    let density = TextDensity::of(&article);
+    assert!(density.is_article(), "{:?}", density);
+    // no og:type or published-time meta, so only the density check can recognise it
+    assert!(looks_like_single_article(&article));
+
+    let listing = Html::parse_document(&fixture("section_listing.html"));
+    let density = TextDensity::of(&listing);
+    assert!(!density.is_article(), "{:?}", density);
+    assert!(density.link_density() > 0.5, "{:?}", density);
+    assert!(!looks_like_single_article(&listing));
+    assert!(!looks_like_single_article(&Html::parse_document(&fixture("huffpost_listing.html"))));
+}
+
+#[test]
+fn per_host_cap_keeps_newest() {
+    let item = |link: &str, day: u32| Item {
+        title: link.into(),
+        link: link.into(),
+        pub_date: Some(format!("2024-04-{:02}T09:00:00Z", day)),
+        ..Default::default()
+    };
+    let mut items = vec![
+        item("https://a.example.com/1", 1),
+        item("https://a.example.com/2", 5),
+        item("https://www.a.example.com/3", 3),
+        item("https://b.example.org/1", 2),
+        item("https://a.example.com/4", 4),
+        item("https://c.example.net/1", 6),
+        item("https://b.example.org/2", 7),
+    ];
+    cap_per_host(&mut items, 2);
+    assert_eq!(items.iter().map(|it| it.link.as_str()).collect::<Vec<_>>(), vec![
+        "https://b.example.org/2",
+        "https://c.example.net/1",
+        "https://a.example.com/2",
+        "https://a.example.com/4",
+        "https://b.example.org/1",
+    ]);
+}
+
+#[test]
+fn require_image_keeps_illustrated_items() {
+    init_opts();
+    let base = url("https://www.huffpost.com/news/");
+    let listing = Html::parse_document(&fixture("huffpost_listing.html"));
+    let fetcher = MapFetcher::new(&[
+        ("https://www.huffpost.com/entry/storm-closes-schools_n_65f1a2b3c4d5e6f7a8b90001", &fixture("huffpost_entry_1.html")),
+        ("https://www.huffpost.com/entry/museum-returns-artifacts_n_65f1a2b3c4d5e6f7a8b90002", &fixture("huffpost_entry_2.html")),
+    ]);
+    let mut items = extract_from_html(&fetcher, &listing, &base, 20);
+    items.push(Item { title: "Text-only brief".into(), link: "https://www.huffpost.com/entry/brief_n_1".into(), ..Default::default() });
+    drop_imageless(&mut items);
+    // both extracted entries found an image (one via og:image, one via a page <img>)
+    assert_eq!(items.iter().map(|it| it.image.as_deref().unwrap()).collect::<Vec<_>>(), vec![
+        "https://img.huffingtonpost.com/storm.jpg",
+        "https://www.huffpost.com/images/artifacts.jpg",
+    ]);
+}
+
+#[test]
+fn breadcrumb_sections_as_categories() {
+    init_opts();
+    let base = url("https://www.example.com/");
+    let cand = url("https://www.example.com/uk/transport/2024/05/07/trains-full-timetable?utm_source=rss");
+    let doc = Html::parse_document(&fixture("breadcrumbs.html"));
+    let mut items = Vec::new();
+    extract_item_from_doc(&doc, &cand, &base, &mut items);
+    assert_eq!(items[0].categories, vec!["UK News", "Transport"]);
+
+    // JSON-LD articleSection/keywords take precedence over the breadcrumb trail
+    let article = json_ld_to_item(&serde_json::json!({
+        "@type": "NewsArticle", "headline": "Trains", "articleSection": "Transport",
+        "keywords": "rail, timetables,  Transport ,"
+    }), &base).unwrap();
+    assert_eq!(article.categories, vec!["Transport", "rail", "timetables"]);
+
+    // legacy news_keywords and article:tag metas beat the trail, deduplicated
+    let doc = Html::parse_document(r#"<html><head><title>Rail fares frozen</title>
+        <meta name="news_keywords" content="rail, fares , Budget,,">
+        <meta property="article:tag" content="budget"><meta property="article:tag" content="Commuters">
+        </head><body><h1>Rail fares frozen</h1></body></html>"#);
+    let mut items = Vec::new();
+    extract_item_from_doc(&doc, &cand, &base, &mut items);
+    assert_eq!(items[0].categories, vec!["rail", "fares", "Budget", "Commuters"]);
+}
+
+#[test]
+fn article_fragment_cruft_stripped() {
+    init_opts();
+    let html = r#"<div class="article-body">
+        <p>The council voted on Tuesday to keep the library open.</p>
+        <script>window.dataLayer.push({event: "article_view"});</script>
+        <ins class="adsbygoogle" data-ad-slot="123">Advertisement</ins>
+        <div class="newsletter-signup"><h3>Get the morning briefing</h3><form>Email</form></div>
+        <p>Opening hours will be extended from May.</p>
+        <aside class="related-stories"><a href="/x">Read next: budget row</a></aside>
+        <div id="social-share-bar">Share on Facebook</div>
+        <iframe src="https://tracker.example/pixel"></iframe>
+    </div>"#;
+    assert_eq!(fragment_text(html).as_deref(),
+        Some("The council voted on Tuesday to keep the library open. Opening hours will be extended from May."));
+
+    // a custom list replaces the defaults
+    let doc = Html::parse_fragment(html);
+    let text = sanitized_text(&doc, &[".newsletter-signup".to_string()]);
+    assert!(text.contains("Advertisement") && !text.contains("morning briefing"));
+    assert!(css_selector("div[").is_err());
+}
+
+#[test]
+fn first_body_paragraph_as_description() {
+    init_opts();
+    let doc = Html::parse_document(r#"<html><head><title>Harbour Wall Repairs Begin</title></head><body>
+        <nav><p>Home | News | Sport | Weather and travel updates for the whole coastal region today</p></nav>
+        <article>
+          <h1>Harbour Wall Repairs Begin</h1>
+          <p class="byline">By Sam Reid, Coastal Reporter</p>
+          <figure><img src="/wall.jpg"><figcaption><p>The damaged section of the harbour wall seen from the lifeboat station on Monday.</p></figcaption></figure>
+          <p>Advertisement</p>
+          <p>Work to repair the storm-damaged harbour wall started on Monday and is expected to take six weeks, with the slipway closed throughout.</p>
+          <p>The council said fishing boats would be moved to the outer moorings.</p>
+        </article></body></html>"#);
+    assert_eq!(first_paragraph(&doc).as_deref(), Some(
+        "Work to repair the storm-damaged harbour wall started on Monday and is expected to take six weeks, with the slipway closed throughout."));
+    assert_eq!(first_paragraph(&Html::parse_document("<html><body><p>Too short.</p></body></html>")), None);
+}
+
+#[test]
+fn host_timeout_overrides() {
+    init_opts();
+    let overrides: Vec<(String, u64)> = ["slow.example.com=30000", "www.Example.com=2500", "api.slow.example.com=60000"]
+        .iter().map(|s| host_timeout_arg(s).unwrap()).collect();
+    let ms = |u: &str| host_timeout(&url(u), &overrides).map(|d| d.as_millis());
+    assert_eq!(ms("https://example.com/a"), Some(2500));
+    assert_eq!(ms("https://www.example.com/a"), Some(2500));
+    assert_eq!(ms("https://slow.example.com/a"), Some(30000));
+    assert_eq!(ms("https://api.slow.example.com/a"), Some(60000));
+    assert_eq!(ms("https://example.org/a"), None);
+    assert!(host_timeout_arg("example.com").is_err());
+    assert!(host_timeout_arg("example.com=soon").is_err());
+
+    // without an override the request carries no timeout of its own and the client's applies
+    let client = Client::new();
+    let req = request_with_headers(&client, reqwest::Method::GET, &url("https://example.com/")).build().unwrap();
+    assert_eq!(req.timeout(), None);
+    let req = client.get("https://example.com/").timeout(host_timeout(&url("https://example.com/"), &overrides).unwrap()).build().unwrap();
+    assert_eq!(req.timeout(), Some(&Duration::from_millis(2500)));
+}
+
+#[test]
+fn error_words_in_real_headlines_kept() {
+    init_opts();
+    let body = "<p>".to_string() + &"Officials spent the week explaining how the decision was reached and what comes next. ".repeat(6) + "</p>";
+    let page = |title: &str, body: &str| Html::parse_document(&format!("<html><head><title>{}</title></head><body><h1>{}</h1>{}</body></html>", title, title, body));
+    for title in [
+        "Something Went Wrong at the Fed",
+        "Sorry, Not Sorry: The Return of the Non-Apology",
+        "Error 404: How a Missing Page Became a Meme",
+        "Oops! Council Approves the Wrong Budget",
+    ] {
+        assert!(!is_error_page(&page(title, &body), title, &None), "{}", title);
+    }
+    // the same wording on a near-empty page is an error page
+    for title in ["Something went wrong", "Sorry, we couldn't load this", "Error 404"] {
+        assert!(is_error_page(&page(title, "<p>Try again later.</p>"), title, &None), "{}", title);
+    }
+    // whole phrases stay decisive even with content around them
+    assert!(is_error_page(&page("Page Not Found", &body), "Page Not Found", &None));
+}
+
+#[test]
+fn consent_walls_detected() {
+    init_opts();
+    let article_body = "<p>".to_string() + &"The restored bridge carried its first commuters on Thursday after two years of work. ".repeat(6) + "</p>";
+    let page = |title: &str, body: &str| Html::parse_document(&format!("<html><head><title>{}</title></head><body>{}</body></html>", title, body));
+
+    // OneTrust and Didomi interstitials that kept the article's title
+    let onetrust = page("Harbour Bridge Reopens", r#"<div id="onetrust-consent-sdk"><div id="onetrust-banner-sdk">
+        <p>We and our partners use cookies to personalise content.</p><button>Accept all</button></div></div>"#);
+    let didomi = page("Harbour Bridge Reopens", r#"<div id="didomi-host"><h1>Your privacy</h1><button>Agree and close</button></div>"#);
+    // a bare consent form, no known CMP
+    let form = page("Before you continue", r#"<form action="/consent"><p>We use cookies and data to deliver
+        and maintain our services.</p><button>Reject all</button><button>Accept all</button></form>"#);
+    for (name, doc) in [("onetrust", &onetrust), ("didomi", &didomi), ("form", &form)] {
+        assert!(is_consent_wall(doc), "{}", name);
+    }
+
+    // a real article with a cookie banner on top, or a newsletter form, is not a wall
+    let with_banner = page("Harbour Bridge Reopens", &format!(r#"<div class="cookie-consent">We use cookies.</div><article>{}</article>"#, article_body));
+    let newsletter = page("Harbour Bridge Reopens", &format!(r#"<article>{}</article><form><p>Get the morning briefing</p><input name="email"></form>"#, article_body));
+    assert!(!is_consent_wall(&with_banner));
+    assert!(!is_consent_wall(&newsletter));
+}
+
+#[test]
+fn soft_404_pages_skipped() {
+    init_opts();
+    let base = url("https://www.harbourtimes.example/");
+    for (name, cand) in [
+        ("soft404_canonical.html", "https://www.harbourtimes.example/2024/02/11/pier-reopens"),
+        ("soft404_noindex.html", "https://www.harbourtimes.example/2023/12/01/withdrawn-story"),
+    ] {
+        let doc = Html::parse_document(&fixture(name));
+        // neither trips the classic error-page check
+        let title = doc.select(&Selector::parse("title").unwrap()).next().unwrap().text().collect::<String>();
+        assert!(!is_error_page(&doc, &title, &None), "{}", name);
+        assert!(is_soft_404(&doc, &url(cand)), "{}", name);
+        let mut items = Vec::new();
+        extract_item_from_doc(&doc, &url(cand), &base, &mut items);
+        assert!(items.is_empty(), "{}", name);
+    }
+
+    // real articles, including thin ones and the homepage itself, are kept
+    let article = Html::parse_document(&fixture("huffpost_entry_1.html"));
+    assert!(!is_soft_404(&article, &url("https://www.huffpost.com/entry/storm-closes-schools_n_65f1a2b3c4d5e6f7a8b90001")));
+    let home = Html::parse_document(&fixture("soft404_canonical.html"));
+    assert!(!is_soft_404(&home, &base));
+}
+
+// Records what reaches it and how much had arrived at each flush.
+#[derive(Default)]
+struct FlushLog {
+    data: Vec<u8>,
+    flushed_at: Vec<usize>,
+}
+
+impl Write for FlushLog {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.data.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.flushed_at.push(self.data.len());
+        Ok(())
+    }
+}
+
+#[test]
+fn streamed_items_match_final_list() {
+    init_opts();
+    let base = url("https://www.example.com/");
+    let channel = channel_metadata(&Html::parse_document("<title>Example</title>"), &base, &[]);
+    let item = |title: &str, path: &str| Item {
+        title: title.into(),
+        link: format!("https://www.example.com/{}", path),
+        description: Some(format!("About {}.", title)),
+        ..Default::default()
+    };
+    // in discovery order, as the crawl appends them
+    let found = vec![
+        item("Harbour Bridge Reopens", "2024/05/01/bridge"),
+        item("Subscribe Now for Unlimited Access", "2024/05/01/offer"),
+        item("Harbour Bridge Reopens", "2024/05/01/bridge?utm_source=rss"),
+        item("Tip Line", "mailto:tips@example.com"),
+        item("Cycling Lanes Approved", "2024/05/02/cycling-lanes"),
+    ];
+
+    // ndjson: a stream after every fetch
+    let mut streamed = Vec::new();
+    let mut written = HashSet::new();
+    for n in 1..=found.len() {
+        let ready = stream_ready_items(&base, &channel, &found[..n], ItemFilter::from_opts());
+        write_json_items(&mut streamed, &base, &ready, &mut written, false).unwrap();
+    }
+    // the end-of-run list every other format writes
+    let mut items = found.clone();
+    filter_items(&base, &mut items);
+    shape_items(&mut items);
+    let mut written_final = Vec::new();
+    write_json_items(&mut written_final, &base, &items, &mut HashSet::new(), false).unwrap();
+
+    assert_eq!(items.len(), 2);
+    assert_eq!(String::from_utf8(streamed).unwrap(), String::from_utf8(written_final).unwrap());
+}
+
+#[test]
+fn streamed_items_complete_under_small_buffer() {
+    init_opts();
+    let base = url("https://www.example.com/");
+    let items: Vec<Item> = (1..=3).map(|i| Item {
+        title: format!("Story number {} with a title longer than the buffer", i),
+        link: format!("https://www.example.com/2024/05/0{}/story-{}", i, i),
+        description: Some("Ünïcödé text so multi-byte characters straddle buffer boundaries.".into()),
+        ..Default::default()
+    }).collect();
+
+    for pretty in [false, true] {
+        let mut out = io::BufWriter::with_capacity(8, FlushLog::default());
+        let mut written = HashSet::new();
+        write_json_items(&mut out, &base, &items, &mut written, pretty).unwrap();
+        // a second pass over the same items writes nothing
+        write_json_items(&mut out, &base, &items, &mut written, pretty).unwrap();
+        assert!(out.buffer().is_empty());
+        let log = out.into_inner().unwrap_or_else(|_| panic!("flush failed"));
+
+        // one flush per item, each landing exactly on the end of a complete document
+        assert_eq!(log.flushed_at.len(), 3);
+        let text = String::from_utf8(log.data).unwrap();
+        let mut start = 0;
+        for (i, end) in log.flushed_at.into_iter().enumerate() {
+            let doc: JsonValue = serde_json::from_str(&text[start..end]).unwrap();
+            assert_eq!(doc["link"], items[i].link.as_str());
+            assert!(text[..end].ends_with('\n'));
+            start = end;
+        }
+        assert_eq!(start, text.len());
+    }
+}
+
+#[test]
+fn preview_lists_every_field() {
+    let it = Item {
+        title: "Harbour Bridge Reopens".into(),
+        link: "https://www.example.com/2024/03/14/bridge".into(),
+        description: Some("A long description that must not be truncated in the preview output at all.".into()),
+        images: vec!["https://www.example.com/a.jpg".into(), "https://www.example.com/b.jpg".into()],
+        categories: vec!["Transport".into()],
+        word_count: Some(640),
+        ..Default::default()
+    };
+    let text = preview_item(&it, 2, 5);
+    assert!(text.starts_with("Preview of item 2 of 5\n"));
+    assert!(text.contains("  title:          Harbour Bridge Reopens\n"));
+    assert!(text.contains("  description:    A long description that must not be truncated in the preview output at all.\n"));
+    assert!(text.contains("  images:         https://www.example.com/a.jpg\n                  https://www.example.com/b.jpg\n"));
+    assert!(text.contains("  categories:     Transport\n"));
+    assert!(text.contains("  word count:     640\n"));
+    assert!(text.contains("  author:         -\n"));
+    assert_eq!(text.lines().count(), 30);
+    assert!(Args::try_parse_from(["html2rss", "--preview-index", "0", "--preview", "https://example.com/"]).is_err());
+}
+
+#[test]
+fn picture_sources_pick_largest() {
+    init_opts();
+    let cand = url("https://www.example.com/2024/06/01/festival");
+    let doc = Html::parse_document(r#"<html><head><title>Festival Opens</title></head><body>
+        <picture>
+          <source type="image/avif" srcset="/img/festival-800.avif 800w, /img/festival-2400.avif 2400w">
+          <source type="image/webp" srcset="/img/festival-800.webp 800w, /img/festival-1600.webp 1600w">
+          <source media="(min-width: 600px)" srcset="/img/festival-1200.jpg 1200w, /img/festival-1800.jpg 1800w">
+          <img src="/img/festival-400.jpg" srcset="/img/festival-600.jpg 600w" alt="">
+        </picture></body></html>"#);
+    let img = doc.select(&Selector::parse("img").unwrap()).next().unwrap();
+    assert_eq!(best_image_source(img, &cand, false).as_deref(), Some("https://www.example.com/img/festival-2400.avif"));
+    assert_eq!(best_image_source(img, &cand, true).as_deref(), Some("https://www.example.com/img/festival-1800.jpg"));
+
+    // the og-absent fallback uses it too
+    let mut items = Vec::new();
+    extract_item_from_doc(&doc, &cand, &url("https://www.example.com/"), &mut items);
+    assert_eq!(items[0].image.as_deref(), Some("https://www.example.com/img/festival-2400.avif"));
+
+    // density descriptors, and a lazy <img> with only a srcset
+    let doc = Html::parse_document(r#"<img src="data:image/gif;base64,R0lGOD" srcset="/a.jpg 1x, /a@2x.jpg 2x">"#);
+    let img = doc.select(&Selector::parse("img").unwrap()).next().unwrap();
+    assert_eq!(best_image_source(img, &cand, false).as_deref(), Some("https://www.example.com/a@2x.jpg"));
+}
+
+#[test]
+fn checkpoint_resumes_interrupted_crawl() {
+    init_opts();
+    let base = url("https://www.huffpost.com/news/");
+    let path = env::temp_dir().join(format!("html2rss-checkpoint-test-{}.json", std::process::id()));
+    let _ = fs::remove_file(&path);
+    let first = "https://www.huffpost.com/entry/storm-closes-schools_n_65f1a2b3c4d5e6f7a8b90001";
+    let second = "https://www.huffpost.com/entry/museum-returns-artifacts_n_65f1a2b3c4d5e6f7a8b90002";
+    let candidates = [url(first), url(second)];
+    let pages = [(first, fixture("huffpost_entry_1.html")), (second, fixture("huffpost_entry_2.html"))];
+    let pages: Vec<(&str, &str)> = pages.iter().map(|(u, b)| (*u, b.as_str())).collect();
+
+    // first run stops after one page (a budget of one stands in for Ctrl-C)
+    let inner = MapFetcher::new(&pages);
+    let visited = VisitedFetcher::new(&inner);
+    start_checkpoint(path.clone(), "https://www.huffpost.com/news/", &visited);
+    let mut items = checkpoint_items();
+    fetch_candidates(&visited, &candidates, &base, 1, &mut items);
+    assert_eq!(items.len(), 1);
+    assert!(path.exists());
+    let first_run = items;
+
+    // the rerun restores that item and fetches only the page it had not reached
+    let inner = MapFetcher::new(&pages);
+    let visited = VisitedFetcher::new(&inner);
+    start_checkpoint(path.clone(), "https://www.huffpost.com/news/", &visited);
+    let mut items = checkpoint_items();
+    assert_eq!(items, first_run);
+    fetch_candidates(&visited, &candidates, &base, 10, &mut items);
+    assert_eq!(*inner.log.borrow(), vec![format!("GET {}", second)]);
+    assert_eq!(items.iter().map(|it| it.link.as_str()).collect::<Vec<_>>(), vec![first, second]);
+
+    // a checkpoint from another start URL is not used; completion removes the file
+    let other = Checkpoint::load(path.clone(), "https://www.example.com/");
+    assert!(other.processed.is_empty() && other.items.is_empty());
+    finish_checkpoint();
+    assert!(!path.exists());
+}
+
+#[test]
+fn trailing_slash_variants_collapse() {
+    init_opts();
+    let pairs = [
+        ("https://www.example.com/2024/05/07/trains", "https://www.example.com/2024/05/07/trains/"),
+        ("https://www.example.com/story/42?utm_source=x", "https://www.example.com/story/42/#comments"),
+    ];
+    for policy in [SlashPolicy::Add, SlashPolicy::Remove] {
+        for (a, b) in pairs {
+            assert_eq!(canonicalize_url_with(a, policy), canonicalize_url_with(b, policy), "{:?}", policy);
+        }
+    }
+    assert_eq!(canonicalize_url_with(pairs[0].0, SlashPolicy::Add), "https://www.example.com/2024/05/07/trains/");
+    assert_eq!(canonicalize_url_with(pairs[0].1, SlashPolicy::Remove), "https://www.example.com/2024/05/07/trains");
+    assert_ne!(canonicalize_url_with(pairs[0].0, SlashPolicy::Leave), canonicalize_url_with(pairs[0].1, SlashPolicy::Leave));
+    // files and the root keep their form
+    assert_eq!(canonicalize_url_with("https://www.example.com/a/page.html", SlashPolicy::Add), "https://www.example.com/a/page.html");
+    assert_eq!(canonicalize_url_with("https://www.example.com/", SlashPolicy::Remove), "https://www.example.com/");
+
+    let base = url("https://www.example.com/");
+    let doc = Html::parse_document(r#"<a href="/world/">World</a><a href="/2024/05/07/trains/">Trains</a>
+        <a href="/2024/05/06/bridge/">Bridge</a><a href="/about">About</a><a href="/feed.xml">RSS</a>
+        <a href="https://other.example.org/x">Elsewhere</a>"#);
+    assert_eq!(slash_convention(&doc, &base), SlashPolicy::Add);
+    assert_eq!(slash_convention(&Html::parse_document("<p>No links</p>"), &base), SlashPolicy::Leave);
+}
+
+#[test]
+fn linked_feed_serving_html_falls_back() {
+    init_opts();
+    let base = url("https://gazette.example.net/");
+    let doc = Html::parse_document(r#"<html><head>
+        <link rel="alternate" type="application/rss+xml" href="/feed"></head><body></body></html>"#);
+
+    let broken = MapFetcher::new(&[("https://gazette.example.net/feed", "<!DOCTYPE html><html><body><h1>Latest news</h1></body></html>")]);
+    assert!(fetch_linked_feed(&broken, &doc, &base).is_none());
+    assert_eq!(broken.log.borrow().as_slice(), ["GET https://gazette.example.net/feed"]);
+
+    let working = MapFetcher::new(&[("https://gazette.example.net/feed", &fixture("start_feed.xml"))]);
+    let page = fetch_linked_feed(&working, &doc, &base).expect("real feed passed through");
+    assert!(page.body.contains("Library Extends Weekend Hours"));
+
+    // --repair-feed: the linked feed's entries are filtered like crawled items
+    let read = |filter: ItemFilter| feed_source_items(&page.body, &page.url, &base, filter);
+    assert_eq!(read(ItemFilter::from_opts()).len(), 2);
+    assert!(read(ItemFilter { require_image: true, ..ItemFilter::from_opts() }).is_empty());
+    assert_eq!(read(ItemFilter { max_items_per_host: Some(1), ..ItemFilter::from_opts() }).len(), 1);
+}
+
+#[test]
+fn emoji_stripped_from_titles() {
+    let cases = [
+        ("🔥 Hot deals this weekend 🔥", "Hot deals this weekend"),
+        ("Team wins the cup 🏆🎉 after extra time", "Team wins the cup after extra time"),
+        ("Family 👨‍👩‍👧 moves into new home 👍🏽", "Family moves into new home"),
+        ("Weather: ☀️ sunny, 25°C", "Weather: sunny, 25°C"),
+        ("Café prices rise — again", "Café prices rise — again"),
+    ];
+    for (title, want) in cases {
+        assert_eq!(strip_symbols(title, SymbolMode::Remove), want, "{}", title);
+    }
+    assert_eq!(strip_symbols("© 2024 Gazette ™", SymbolMode::Remove), "© 2024 Gazette");
+    assert_eq!(strip_symbols("Rain☔tonight", SymbolMode::Remove), "Raintonight");
+    assert_eq!(strip_symbols("Rain☔tonight", SymbolMode::Space), "Rain tonight");
+    assert_eq!(strip_symbols("Flags 🇬🇧🇫🇷 at the summit", SymbolMode::Space), "Flags at the summit");
+}
+
+#[test]
+fn download_budget_stops_crawl() {
+    init_opts();
+    let base = url("https://news.example.org/");
+    let links: String = (1..=4).map(|n| format!(r#"<a href="/2024/05/0{n}/story-{n}"><img src="/{n}.jpg">Story {n}</a>"#)).collect();
+    let start = Html::parse_document(&format!("<html><body>{}</body></html>", links));
+    let pages: Vec<(String, String)> = (1..=4).map(|n| (
+        format!("https://news.example.org/2024/05/0{n}/story-{n}"),
+        format!(r#"<html><head><meta property="og:title" content="Story number {n}"></head><body><p>{}</p></body></html>"#, "x".repeat(900)),
+    )).collect();
+    let refs: Vec<(&str, &str)> = pages.iter().map(|(u, b)| (u.as_str(), b.as_str())).collect();
+    let inner = MapFetcher::new(&refs);
+
+    // each page is ~1 KB: the second fetch crosses 1.5 KB and is kept, nothing after it is requested
+    let budget = BudgetFetcher::new(&inner, Some(1500));
+    let items = extract_from_html(&budget, &start, &base, 10);
+    assert_eq!(items.len(), 2);
+    assert_eq!(inner.log.borrow().iter().filter(|l| l.starts_with("GET")).count(), 2);
+    let after = budget.get(&url("https://news.example.org/2024/05/04/story-4"));
+    assert!(matches!(after.err().and_then(|e| e.downcast::<FetchError>().ok()).as_deref(), Some(FetchError::BudgetExhausted(1500))));
+
+    let unlimited = BudgetFetcher::new(&inner, None);
+    assert_eq!(extract_from_html(&unlimited, &start, &base, 10).len(), 4);
+}
+
+#[test]
+fn oembed_enriches_items() {
+    init_opts();
+    let page_url = url("https://video.example.com/watch/8812");
+    let doc = Html::parse_document(r#"<html><head><title>Watch: Harbour Lights | VideoSite - Free Videos</title>
+        <link rel="alternate" type="application/json+oembed" href="/oembed?url=https%3A%2F%2Fvideo.example.com%2Fwatch%2F8812&amp;format=json">
+        </head><body></body></html>"#);
+    let endpoint = "https://video.example.com/oembed?url=https%3A%2F%2Fvideo.example.com%2Fwatch%2F8812&format=json";
+    let fetcher = MapFetcher::new(&[(endpoint, r#"{"type": "video", "version": "1.0", "title": "Harbour Lights",
+        "author_name": "Mara Quay", "thumbnail_url": "https://i.video.example.com/8812/hq.jpg",
+        "html": "<iframe src=\"https://video.example.com/embed/8812\"></iframe><p>A timelapse of the harbour at night.</p>"}"#)]);
+    let scraped = || vec![Item {
+        title: "Watch: Harbour Lights | VideoSite - Free Videos".into(), link: page_url.to_string(),
+        image: Some("https://video.example.com/logo.png".into()), images: vec!["https://video.example.com/logo.png".into()],
+        ..Default::default()
+    }];
+
+    let mut items = scraped();
+    enrich_from_oembed(&fetcher, &doc, &page_url, &mut items);
+    assert_eq!(items[0].title, "Harbour Lights");
+    assert_eq!(items[0].author.as_deref(), Some("Mara Quay"));
+    assert_eq!(items[0].image.as_deref(), Some("https://i.video.example.com/8812/hq.jpg"));
+    assert_eq!(items[0].images.len(), 2);
+    assert_eq!(items[0].description.as_deref(), Some("A timelapse of the harbour at night."));
+
+    // no discovery link: nothing is fetched and the scraped item stands
+    let plain = Html::parse_document("<html><head><title>Harbour Lights</title></head></html>");
+    let mut items = scraped();
+    enrich_from_oembed(&fetcher, &plain, &page_url, &mut items);
+    assert_eq!(items, scraped());
+    assert_eq!(fetcher.log.borrow().len(), 1);
+}
+
+#[test]
+fn categories_and_images_keep_source_order() {
+    init_opts();
+    let base = url("https://www.example.com/");
+    let cand = url("https://www.example.com/2024/05/07/harbour-festival");
+    let page = r#"<html><head>
+        <meta name="news_keywords" content="Festivals, harbour, Music, festivals, Food">
+        <meta property="article:tag" content="Music"><meta property="article:tag" content="Boats">
+        <script type="application/ld+json">{"@type": "NewsArticle", "headline": "Harbour Festival Returns",
+            "keywords": ["Zebra", "Apple", "Mango"],
+            "image": ["https://www.example.com/img/c.jpg", "https://www.example.com/img/a.jpg", "https://www.example.com/img/c.jpg", "https://www.example.com/img/b.jpg"]}</script>
+        </head><body></body></html>"#;
+
+    let render = || {
+        let doc = Html::parse_document(page);
+        let mut items = Vec::new();
+        extract_item_from_doc(&doc, &cand, &base, &mut items);
+        (items.clone(), rss_document(&base, &Channel::default(), &items).unwrap())
+    };
+    let (items, first) = render();
+    assert_eq!(items[0].categories, vec!["Zebra", "Apple", "Mango", "Festivals", "harbour", "Music", "Food", "Boats"]);
+    assert_eq!(items[0].images, vec!["https://www.example.com/img/c.jpg", "https://www.example.com/img/a.jpg", "https://www.example.com/img/b.jpg"]);
+    for _ in 0..5 {
+        assert_eq!(render().1, first);
+    }
+}
+
+#[test]
+fn hreflang_variant_preferred() {
+    let page_url = url("https://www.example.com/en/2024/05/07/harbour-festival");
+    let doc = Html::parse_document(r#"<html lang="en"><head>
+        <link rel="alternate" hreflang="en" href="/en/2024/05/07/harbour-festival">
+        <link rel="alternate" hreflang="de-DE" href="../../../../de/2024/05/07/hafenfest">
+        <link rel="alternate" hreflang="de-AT" href="https://www.example.at/2024/05/07/hafenfest">
+        <link rel="alternate" hreflang="pt" href="/pt/2024/05/07/festa-do-porto">
+        <link rel="alternate" hreflang="x-default" href="/2024/05/07/harbour-festival">
+        </head><body></body></html>"#);
+    let variant = |lang: &str| hreflang_variant(&doc, &page_url, lang).map(|u| u.to_string());
+    assert_eq!(variant("de-AT").as_deref(), Some("https://www.example.at/2024/05/07/hafenfest"));
+    assert_eq!(variant("de").as_deref(), Some("https://www.example.com/de/2024/05/07/hafenfest"));
+    assert_eq!(variant("pt-BR").as_deref(), Some("https://www.example.com/pt/2024/05/07/festa-do-porto"));
+    // the page is already English; no variant for French
+    assert_eq!(variant("en-GB"), None);
+    assert_eq!(variant("fr"), None);
+    assert!(!lang_matches("pt-PT", "pt-BR"));
+}
+
+#[test]
+fn feed_image_source_preference() {
+    init_opts();
+    let base = url("https://www.example.com/");
+    let doc = Html::parse_document(r#"<html><head>
+        <meta property="og:image" content="/img/2024/05/top-story.jpg">
+        <link rel="icon" href="/favicon.ico"><link rel="apple-touch-icon" sizes="180x180" href="/apple-touch-icon.png">
+        <script type="application/ld+json">{"@type": "NewsMediaOrganization", "name": "Harbour Gazette",
+            "logo": {"@type": "ImageObject", "url": "https://www.example.com/brand/logo.png"}}</script>
+        </head></html>"#);
+    let nodes = json_ld_nodes(&doc, &base);
+    let logo = |order: &[ImageSource]| channel_logo(&doc, &nodes, &base, order);
+    assert_eq!(logo(&opts().feed_image_from).as_deref(), Some("https://www.example.com/brand/logo.png"));
+    assert_eq!(logo(&[ImageSource::Og, ImageSource::Jsonld]).as_deref(), Some("https://www.example.com/img/2024/05/top-story.jpg"));
+    assert_eq!(logo(&[ImageSource::Icon]).as_deref(), Some("https://www.example.com/apple-touch-icon.png"));
+    // a missing source falls through to the next
+    let bare = Html::parse_document(r#"<link rel="shortcut icon" href="/favicon.ico">"#);
+    assert_eq!(channel_logo(&bare, &[], &base, &[ImageSource::Jsonld, ImageSource::Og, ImageSource::Icon]).as_deref(),
+        Some("https://www.example.com/favicon.ico"));
+    assert_eq!(Args::try_parse_from(["html2rss", "--feed-image-from", "og,icon", "https://example.com/"]).unwrap().feed_image_from,
+        vec![ImageSource::Og, ImageSource::Icon]);
+
+    let channel = Channel { logo: logo(&[ImageSource::Jsonld]), publisher: Some("Harbour Gazette".into()), ..Default::default() };
+    let xml = String::from_utf8(rss_document(&base, &channel, &[]).unwrap()).unwrap();
+    assert!(xml.contains("<image>\n      <url>https://www.example.com/brand/logo.png</url>\n      <title>Harbour Gazette</title>"), "{}", xml);
+}
+
+#[test]
+fn undated_items_get_staggered_dates() {
+    let mut items: Vec<Item> = ["First found", "Dated", "Second found", "Bad date", "Third found"].iter()
+        .map(|t| Item { title: t.to_string(), ..Default::default() })
+        .collect();
+    items[1].pub_date = Some("2024-03-15T10:00:00Z".into());
+    items[3].pub_date = Some("last Tuesday".into());
+    let now = DateTime::parse_from_rfc3339("2024-05-07T12:00:00Z").unwrap().with_timezone(&chrono::Utc);
+    stamp_undated(&mut items, now);
+    let dates: Vec<_> = items.iter().map(|it| it.pub_date.as_deref().unwrap()).collect();
+    assert_eq!(dates, ["2024-05-07T12:00:00Z", "2024-03-15T10:00:00Z", "2024-05-07T11:59:59Z", "2024-05-07T11:59:58Z", "2024-05-07T11:59:57Z"]);
+
+    // newest-first sorting keeps discovery order among the stamped items
+    sort_newest_first(&mut items);
+    assert_eq!(items.iter().map(|it| it.title.as_str()).collect::<Vec<_>>(),
+        ["First found", "Second found", "Bad date", "Third found", "Dated"]);
+}
+
+#[test]
+fn twitter_card_player_and_image() {
+    init_opts();
+    let base = url("https://video.example.com/");
+    let cand = url("https://video.example.com/watch/2024/05/07/harbour-lights");
+    let doc = Html::parse_document(r#"<html><head><title>Harbour Lights</title>
+        <meta name="twitter:card" content="player">
+        <meta name="twitter:title" content="Harbour Lights at Night">
+        <meta name="twitter:image" content="https://i.video.example.com/8812/thumb_120.jpg">
+        <meta name="twitter:image:src" content="https://i.video.example.com/8812/maxres.jpg">
+        <meta name="twitter:player" content="https://video.example.com/embed/8812">
+        <meta name="twitter:player:width" content="1280"><meta name="twitter:player:height" content="720">
+        <meta name="twitter:player:stream" content="/media/8812/720p.mp4">
+        <meta name="twitter:player:stream:content_type" content="video/mp4; codecs=&quot;avc1.42E01E&quot;">
+        </head><body></body></html>"#);
+    let mut items = Vec::new();
+    extract_item_from_doc(&doc, &cand, &base, &mut items);
+    let it = &items[0];
+    assert_eq!(it.image.as_deref(), Some("https://i.video.example.com/8812/maxres.jpg"));
+    assert_eq!(it.player.as_deref(), Some("https://video.example.com/embed/8812"));
+    assert_eq!(it.video.as_deref(), Some("https://video.example.com/media/8812/720p.mp4"));
+
+    let mut w = Writer::new(Vec::new());
+    write_item(&mut w, it, &base).unwrap();
+    let xml = String::from_utf8(w.into_inner()).unwrap();
+    // size unknown: no length unless --enclosure-zero-length, as for image enclosures
+    assert!(xml.contains(r#"<enclosure url="https://video.example.com/media/8812/720p.mp4" type="video/mp4; codecs=&quot;avc1.42E01E&quot;"/>"#), "{}", xml);
+
+    // --probe-enclosures sizes the stream, not the poster image
+    let video = "https://video.example.com/media/8812/720p.mp4";
+    let fetcher = MapFetcher::new(&[]).with_head(video, 200, Some("video/mp4")).with_length(video, 7_340_032);
+    probe_enclosures(&fetcher, &mut items);
+    assert_eq!((items[0].video_length, items[0].image_length), (Some(7_340_032), None));
+    let mut w = Writer::new(Vec::new());
+    write_item(&mut w, &items[0], &base).unwrap();
+    assert!(String::from_utf8(w.into_inner()).unwrap().contains(r#"720p.mp4" length="7340032" type="video/mp4"#));
+
+    // og:image still wins over a later twitter:image
+    let doc = Html::parse_document(r#"<meta property="og:image" content="/og.jpg"><meta name="twitter:image:src" content="/tw.jpg"><title>T</title>"#);
+    let mut items = Vec::new();
+    extract_item_from_doc(&doc, &cand, &base, &mut items);
+    assert_eq!(items[0].image.as_deref(), Some("https://video.example.com/og.jpg"));
+}
+
+#[test]
+fn figcaption_captions_primary_image() {
+    init_opts();
+    let base = url("https://www.example.com/");
+    let cand = url("https://www.example.com/2024/06/02/tall-ships");
+    let doc = Html::parse_document(r#"<html><head><title>Tall Ships Arrive</title>
+        <meta property="og:image" content="/img/ships-1200.jpg"></head><body><article>
+        <figure><img src="/img/crowd.jpg"><figcaption>Crowds on the quay.</figcaption></figure>
+        <figure><img src="/img/ships-600.jpg" srcset="/img/ships-600.jpg 600w, /img/ships-1200.jpg 1200w">
+          <figcaption>The fleet enters the harbour at dawn. <span class="photo-credit">Photo: A. Lindqvist</span></figcaption>
+        </figure></article></body></html>"#);
+    let mut items = Vec::new();
+    extract_item_from_doc(&doc, &cand, &base, &mut items);
+    let it = &items[0];
+    assert_eq!(it.image.as_deref(), Some("https://www.example.com/img/ships-1200.jpg"));
+    assert_eq!(it.image_caption.as_deref(), Some("The fleet enters the harbour at dawn."));
+    assert_eq!(it.image_credit.as_deref(), Some("Photo: A. Lindqvist"));
+
+    // survives a checkpoint round trip
+    assert_eq!(checkpoint_to_item(&item_to_checkpoint(it)).as_ref(), Some(it));
+
+    // an image outside any <figure> has no caption
+    let doc = Html::parse_document(r#"<title>T</title><img src="/a.jpg"><figure><img src="/b.jpg"><figcaption>B</figcaption></figure>"#);
+    let mut items = Vec::new();
+    extract_item_from_doc(&doc, &cand, &base, &mut items);
+    assert_eq!((items[0].image_caption.as_deref(), items[0].image_credit.as_deref()), (None, None));
+}
+
+#[test]
+fn unrepairable_mojibake_dropped() {
+    init_opts();
+    let item = |title: &str, desc: Option<&str>| Item {
+        title: clean_title(title), description: desc.map(fix_mojibake),
+        link: format!("https://www.example.com/{}", title.len()), ..Default::default()
+    };
+    let mut items = vec![
+        // repairable: UTF-8 read as Windows-1252 once
+        item("CafÃ© owners welcome the new rules", None),
+        item("SÃO PAULO: metro extends its hours", None),
+        // lost bytes can't come back
+        item("Caf\u{FFFD} owners welcome the new rules", None),
+        item("Mayor says \u{FFFD}no\u{FFFD} to tower plans", Some("Plain text")),
+        item("Budget approved", Some("Itâ€™s official, but CafÃ\u{FFFD} owners object")),
+    ];
+    assert_eq!(items[0].title, "Café owners welcome the new rules");
+    drop_garbled(&mut items);
+    assert_eq!(items.iter().map(|it| it.title.as_str()).collect::<Vec<_>>(),
+        ["Café owners welcome the new rules", "SÃO PAULO: metro extends its hours"]);
+    assert!(looks_garbled("Itâ€™s official"));
+    assert!(!looks_garbled("Ça va, à bientôt — “quoted”"));
+}
+
+#[test]
+fn merge_into_existing_feed() {
+    init_opts();
+    let base = url("https://gazette.example.com/");
+    let path = env::temp_dir().join(format!("html2rss-merge-{}.xml", std::process::id()));
+    let old = |n: u32, day: u32| Item {
+        title: format!("Old story {}", n), link: format!("https://gazette.example.com/2024/03/{:02}/old-{}", day, n),
+        description: Some(format!("Kept from run one ({})", n)), pub_date: Some(format!("2024-03-{:02}T08:00:00Z", day)),
+        image: Some(format!("https://gazette.example.com/img/old-{}.jpg", n)), images: vec![format!("https://gazette.example.com/img/old-{}.jpg", n)],
+        categories: vec!["Harbour".into()],
+        ..Default::default()
+    };
+    let first = vec![old(1, 10), old(2, 11), old(3, 12)];
+    merge_into_file(&path, &base, &Channel::default(), &first, 100).unwrap();
+
+    // the page rotated old-1 off; old-3 comes back re-extracted with a different description
+    let mut again = old(3, 12);
+    again.description = Some("Re-extracted teaser".into());
+    let fresh = Item { title: "New story".into(), link: "https://gazette.example.com/2024/03/14/new".into(),
+        pub_date: Some("2024-03-14T08:00:00Z".into()), ..Default::default() };
+    merge_into_file(&path, &base, &Channel::default(), &[fresh, again], 3).unwrap();
+
+    let xml = fs::read_to_string(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    let items = parse_feed_lenient(&xml, &base);
+    assert_eq!(items.iter().map(|it| it.title.as_str()).collect::<Vec<_>>(), ["New story", "Old story 3", "Old story 2"]);
+    assert_eq!(items[1].description.as_deref(), Some("Kept from run one (3)"));
+    assert_eq!(items[1].image.as_deref(), Some("https://gazette.example.com/img/old-3.jpg"));
+    assert_eq!(items[1].categories, ["Harbour"]);
+    assert_eq!(xml.matches("<guid").count(), 3);
+}
+
+#[test]
+fn live_blog_updates_become_items() {
+    init_opts();
+    let base = url("https://gazette.example.com/2024/07/04/election-live");
+    let doc = Html::parse_document(&fixture("live_blog.html"));
+    let mut items = extract_from_json_ld(&doc, &base).unwrap();
+    assert_eq!(items.iter().map(|it| (it.title.as_str(), it.link.as_str())).collect::<Vec<_>>(), [
+        ("Harbour ward declares: turnout up four points", "https://gazette.example.com/2024/07/04/election-live#post-3"),
+        ("Counting is under way at the leisure centre, with the first boxes opened shortly before midnight.", "https://gazette.example.com/2024/07/04/election-live#post-2"),
+        ("Polls have closed", "https://gazette.example.com/2024/07/04/election-live#update-3"),
+    ]);
+    assert_eq!(items[1].pub_date.as_deref(), Some("2024-07-04T23:40:00+01:00"));
+    assert_eq!(items[1].image.as_deref(), Some("https://gazette.example.com/img/count.jpg"));
+    assert_eq!(items[2].description.as_deref(), Some("Polls closed at 10pm. Stay with us for results through the night."));
+
+    // anchors on the live blog survive link filtering and keep distinct GUIDs
+    filter_items(&base, &mut items);
+    assert_eq!(items.len(), 3);
+    assert_eq!(item_guid(&items[0], &base), ("https://gazette.example.com/2024/07/04/election-live#post-3".to_string(), true));
+
+    // fetched as a candidate page, the live blog expands the same way
+    let mut from_page = Vec::new();
+    extract_item_from_doc(&doc, &base, &url("https://gazette.example.com/"), &mut from_page);
+    assert_eq!(from_page.len(), 3);
+}
+
+// Records the most requests it ever had in flight per host.
+struct SlowFetcher {
+    in_flight: Mutex<HashMap<String, usize>>,
+    peak: Mutex<HashMap<String, usize>>,
+}
+
+impl Fetcher for SlowFetcher {
+    fn get(&self, url: &Url) -> Result<Page, Box<dyn Error>> {
+        let host = url.host_str().unwrap().to_string();
+        {
+            let mut in_flight = self.in_flight.lock().unwrap();
+            let n = in_flight.entry(host.clone()).or_insert(0);
+            *n += 1;
+            let mut peak = self.peak.lock().unwrap();
+            let p = peak.entry(host.clone()).or_insert(0);
+            *p = (*p).max(*n);
+        }
+        std::thread::sleep(Duration::from_millis(20));
+        *self.in_flight.lock().unwrap().get_mut(&host).unwrap() -= 1;
+        Ok(Page { url: url.clone(), body: String::new(), content_type: None })
+    }
+
+    fn head(&self, _url: &Url) -> Result<Head, Box<dyn Error>> {
+        Err("not used".into())
+    }
+}
+
+#[test]
+fn per_host_concurrency_capped() {
+    let inner = SlowFetcher { in_flight: Mutex::new(HashMap::new()), peak: Mutex::new(HashMap::new()) };
+    let limited = HostLimitFetcher::new(&inner, 2);
+    std::thread::scope(|s| {
+        for i in 0..12 {
+            let limited = &limited;
+            let host = if i % 3 == 0 { "b.example.org" } else { "a.example.com" };
+            s.spawn(move || limited.get(&url(&format!("https://{}/story/{}", host, i))).unwrap());
+        }
+    });
+    let peak = inner.peak.lock().unwrap();
+    assert_eq!(peak["a.example.com"], 2);
+    assert!(peak["b.example.org"] <= 2);
+    assert!(limited.active.lock().unwrap().is_empty());
+}
+
+#[test]
+fn validation_warnings_as_comments() {
+    init_opts();
+    let base = url("https://www.example.com/news/");
+    let items = vec![
+        Item { title: "Fine".into(), link: "https://www.example.com/2024/05/07/fine".into(),
+            description: Some("Has everything".into()), pub_date: Some("2024-05-07T08:00:00Z".into()), ..Default::default() },
+        Item { title: "Undated -- and <odd> --".into(), link: "https://www.example.com/2024/05/07/odd?utm_source=x".into(), ..Default::default() },
+        Item { title: "Anchor".into(), link: "https://www.example.com/news/#comments".into(),
+            description: Some(" ".into()), pub_date: Some("yesterday-".into()), ..Default::default() },
+    ];
+    assert_eq!(feed_warnings(&base, &items), [
+        "item 2 (Undated -- and <odd> --): no date; empty description; tracking parameters in link",
+        "item 3 (Anchor): unparseable date \"yesterday-\"; empty description; anchor on the source page",
+    ]);
+
+    let mut w = Writer::new(Vec::new());
+    write_validation_comments(&mut w, &base, &items).unwrap();
+    w.write_event(Event::Empty(BytesStart::new("rss"))).unwrap();
+    let xml = String::from_utf8(w.into_inner()).unwrap();
+    assert!(xml.starts_with("<!-- html2rss validation: 2 of 3 item(s) with warnings -->"), "{}", xml);
+    assert!(xml.contains("<!-- item 2 (Undated - - and <odd> - -): no date;"), "{}", xml);
+    // every comment is well formed: no "--" inside, none ending in "-"
+    for body in xml.split("<!--").skip(1).map(|c| c.split("-->").next().unwrap()) {
+        assert!(!body.contains("--") && !body.ends_with('-'), "{:?}", body);
+    }
+    let mut reader = quick_xml::Reader::from_str(&xml);
+    let mut comments = 0;
+    loop {
+        match reader.read_event().expect("well-formed XML") {
+            Event::Comment(_) => comments += 1,
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    assert_eq!(comments, 3);
+    assert_eq!(xml_comment_text("a---b-"), "a- - -b- ");
+}
+
+#[test]
+fn href_whitespace_cleaned() {
+    init_opts();
+    let base = url("https://www.example.com/");
+    let doc = Html::parse_document("<html><body>
+        <a href=\"
+            /2024/03/14/story/
+            harbour-bridge?id=7
+        \">Harbour bridge reopens to traffic after two years of repairs</a>
+        <a href=\"/2024/03/14/story/harbour-bridge?id=7\">Harbour bridge reopens</a>
+        <a href=\"\t/2024/03/15/story/ferry-timetable \">Ferry timetable changes from Monday morning onwards</a>
+    </body></html>");
+    let links: Vec<String> = build_candidate_list(&doc, &base, 20).iter().map(|u| u.to_string()).collect();
+    assert_eq!(links, ["https://www.example.com/2024/03/14/story/harbour-bridge?id=7", "https://www.example.com/2024/03/15/story/ferry-timetable"]);
+
+    assert_eq!(clean_href("  /a/b\n   /c  "), "/a/b/c");
+    assert_eq!(clean_href("/files/annual report.pdf"), "/files/annual report.pdf");
+    assert!(matches!(clean_href("/plain/path"), Cow::Borrowed(_)));
+}
+
+#[test]
+fn session_scoped_to_start_host() {
+    init_opts();
+    let path = env::temp_dir().join(format!("html2rss-session-{}.json", std::process::id()));
+    fs::write(&path, r#"{"cookies": [{"name": "sid", "value": "1"}, {"name": "cdn", "value": "2", "domain": "cdn.net"}],
+        "headers": {"X-Token": "secret"}}"#).unwrap();
+    let session = load_session(&path, &url("https://example.com/news/")).unwrap();
+    let _ = fs::remove_file(&path);
+
+    assert_eq!(session_cookie_header(&session, &url("https://www.example.com/a")).as_deref(), Some("sid=1"));
+    assert_eq!(session_cookie_header(&session, &url("https://img.cdn.net/a.jpg")).as_deref(), Some("cdn=2"));
+    assert_eq!(session_cookie_header(&session, &url("https://other.org/story")), None);
+    assert_eq!(session_headers(&session, &url("https://example.com/b")).len(), 1);
+    assert!(session_headers(&session, &url("https://www.youtube.com/oembed")).is_empty());
+    assert!(session_headers(&session, &url("https://api.example.com/")).is_empty());
+}
+
+#[test]
+fn host_auth_matches_on_label_boundary() {
+    let path = env::temp_dir().join(format!("html2rss-host-auth-{}.json", std::process::id()));
+    fs::write(&path, r#"{"example.com": {"X-Key": "a"}, ".news.org": {"X-Key": "b"}, "api.news.org": {"X-Key": "c"}}"#).unwrap();
+    let auth = load_host_auth(&path).unwrap();
+    let _ = fs::remove_file(&path);
+    let key = |u: &str| host_auth_for(&auth, &url(u)).map(|p| p[0].1.to_str().unwrap().to_string());
+
+    assert_eq!(key("https://example.com/a").as_deref(), Some("a"));
+    assert_eq!(key("https://www.example.com/a"), None);
+    assert_eq!(key("https://badexample.com/a"), None);
+    assert_eq!(key("https://news.org/").as_deref(), Some("b"));
+    assert_eq!(key("https://eu.news.org/").as_deref(), Some("b"));
+    assert_eq!(key("https://api.news.org/").as_deref(), Some("c"));
+    assert_eq!(key("https://fakenews.org/"), None);
+}
+
+#[test]
+fn full_article_page_still_filtered() {
+    init_opts();
+    let base = url("https://www.example.com/");
+    let articles: String = (0..40).map(|i| format!(
+        "<article><h2>Council approves the harbour plan, part {i}</h2><a href=\"/2024/03/14/story/harbour-{i}\">more</a>\
+         <p>Councillors voted on the harbour plan.</p></article>")).collect();
+    let mailto: String = (0..40).map(|i| format!(
+        "<article><h2>Write to the newsroom about story {i}</h2><a href=\"mailto:desk{i}@example.com\">mail</a></article>")).collect();
+    let fetcher = MapFetcher::new(&[]);
+
+    let doc = Html::parse_document(&format!("<html><body>{}</body></html>", articles));
+    assert_eq!(extract_from_html_with(&fetcher, &doc, &base, 5, ItemFilter::from_opts()).len(), 5);
+    let imageless = extract_from_html_with(&fetcher, &doc, &base, 5, ItemFilter { require_image: true, ..ItemFilter::from_opts() });
+    assert!(imageless.is_empty(), "{:?}", imageless);
+
+    let doc = Html::parse_document(&format!("<html><body>{}</body></html>", mailto));
+    let items = extract_from_html_with(&fetcher, &doc, &base, 5, ItemFilter::from_opts());
+    assert!(items.iter().all(|it| !it.link.starts_with("mailto:")), "{:?}", items);
+}
+
+#[test]
+fn merge_keeps_live_blog_guids() {
+    init_opts();
+    let base = url("https://gazette.example.com/2024/07/04/election-live");
+    let doc = Html::parse_document(&fixture("live_blog.html"));
+    let items = extract_from_json_ld(&doc, &base).unwrap();
+    let linkless = Item { title: "Polling stations open at 7am".into(), link: base.to_string(),
+        description: Some("Doors open across the borough.".into()), ..Default::default() };
+    let path = env::temp_dir().join(format!("html2rss-merge-live-{}.xml", std::process::id()));
+
+    for _ in 0..2 {
+        let mut run = items.clone();
+        run.push(linkless.clone());
+        merge_into_file(&path, &base, &Channel::default(), &run, 100).unwrap();
+    }
+    let xml = fs::read_to_string(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    assert_eq!(xml.matches("<guid").count(), 4, "{}", xml);
+    let read = parse_feed_lenient(&xml, &base);
+    assert!(read.iter().any(|it| it.guid.as_deref() == Some("https://gazette.example.com/2024/07/04/election-live#post-3")));
+}
@@ -1,25 +1,38 @@
-use clap::Parser;
+use clap::{Parser, ValueEnum};
 use quick_xml::events::{BytesDecl, Event};
 use quick_xml::Writer;
 use reqwest::blocking::Client;
-use reqwest::header::{USER_AGENT, ACCEPT, ACCEPT_LANGUAGE, CONNECTION};
+use reqwest::header::{HeaderName, HeaderValue, USER_AGENT, ACCEPT, ACCEPT_LANGUAGE, CONNECTION};
 use scraper::{Html, Selector, ElementRef};
 use regex::Regex;
 use unicode_normalization::UnicodeNormalization;
 use html_escape::decode_html_entities;
+use std::borrow::Cow;
+use std::cell::RefCell;
 use std::collections::HashSet;
 use serde_json::Value as JsonValue;
 use std::error::Error;
 use std::io::{self, Write};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use std::thread::sleep;
 use std::env;
-use rand::{thread_rng, Rng};
+use std::sync::{Condvar, Mutex};
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
 use rand::seq::SliceRandom;
 use url::Url;
 use url::form_urlencoded;
-use once_cell::sync::Lazy;
-use chrono::DateTime;
+use once_cell::sync::{Lazy, OnceCell};
+use chrono::{DateTime, FixedOffset};
+use encoding_rs::{Encoding, UTF_8};
+
+#[cfg(test)]
+mod golden_tests;
 
 /// html2rss - generate a simple RSS feed from a webpage
 #[derive(Parser, Debug)]
@@ -35,84 +48,1100 @@ struct Args {
     /// Timeout in milliseconds for network requests (default: 5000)
     #[arg(short = 't', long = "timeout-ms", default_value_t = 10000)]
     timeout_ms: u64,
+
+    /// Normalize headline casing: smart (fix ALL-CAPS titles), lower (sentence case) or none
+    #[arg(long = "title-case", value_enum, default_value_t = TitleCase::None)]
+    title_case: TitleCase,
+
+    /// Emit Media RSS elements (xmlns:media), e.g. gallery images as <media:content>
+    #[arg(long = "media-rss")]
+    media_rss: bool,
+
+    /// Maximum number of images kept per item (first is the enclosure)
+    #[arg(long = "max-images", default_value_t = 5)]
+    max_images: usize,
+
+    /// Drop near-duplicate items (similar descriptions/titles), keeping the richest one
+    #[arg(long = "dedupe-similar")]
+    dedupe_similar: bool,
+
+    /// Similarity (0.0-1.0) at or above which --dedupe-similar treats items as duplicates
+    #[arg(long = "similarity-threshold", default_value_t = 0.8)]
+    similarity_threshold: f64,
+
+    /// What makes two items the same for exact dedup: the canonicalized link (default), the
+    /// source's own id where it has one, the normalized title, or a hash of title and description
+    #[arg(long = "dedup-by", value_enum, value_name = "KEY", default_value_t = DedupKey::Link)]
+    dedup_by: DedupKey,
+
+    /// Directory for persistent state (caches, checkpoints); defaults to $XDG_STATE_HOME/html2rss
+    #[arg(long = "state-dir", value_name = "DIR")]
+    state_dir: Option<PathBuf>,
+
+    /// Declare the Dublin Core namespace and emit <dc:date> (ISO 8601) next to <pubDate>
+    #[arg(long = "dublin-core")]
+    dublin_core: bool,
+
+    /// Declare the Atom namespace and emit <atom:updated> from dateModified/article:modified_time
+    #[arg(long = "atom-updated")]
+    atom_updated: bool,
+
+    /// JSON file mapping hosts to extra headers, e.g. {"example.com": {"Cookie": "a=b"}}; ".example.com" also covers subdomains
+    #[arg(long = "host-auth-file", value_name = "FILE")]
+    host_auth_file: Option<PathBuf>,
+
+    /// Issue a HEAD request per enclosure to fill in its length (bytes)
+    #[arg(long = "probe-enclosures")]
+    probe_enclosures: bool,
+
+    /// Emit length="0" on enclosures whose size is unknown instead of omitting the attribute
+    #[arg(long = "enclosure-zero-length")]
+    enclosure_zero_length: bool,
+
+    /// Fail (exit 3) unless at least this many items survive filtering
+    #[arg(long = "min-items", default_value_t = 1)]
+    min_items: usize,
+
+    /// Output format: rss (default), rss10 (RDF), table (human-readable columns) or ndjson
+    /// (one JSON object per line, streamed while crawling)
+    #[arg(long = "format", value_enum, default_value_t = OutputFormat::Rss)]
+    format: OutputFormat,
+
+    /// Also parse <noscript> fallback markup for candidate links and images
+    #[arg(long = "use-noscript")]
+    use_noscript: bool,
+
+    /// Maximum number of HTTP redirects to follow per request
+    #[arg(long = "max-redirects", default_value_t = 10)]
+    max_redirects: usize,
+
+    /// Do not follow HTTP redirects at all
+    #[arg(long = "no-redirects", conflicts_with = "max_redirects")]
+    no_redirects: bool,
+
+    /// Trim descriptions to this many --summary-unit units, on a clean boundary
+    #[arg(long = "summary-length", value_name = "N")]
+    summary_length: Option<usize>,
+
+    /// Unit for --summary-length
+    #[arg(long = "summary-unit", value_enum, default_value_t = SummaryUnit::Words)]
+    summary_unit: SummaryUnit,
+
+    /// Only emit items from structured sources (linked feed, JSON-LD); skip the HTML heuristics
+    #[arg(long = "structured-only")]
+    structured_only: bool,
+
+    /// JSON session descriptor (user_agent, cookies, headers); headers and cookies without a domain go to the start host only
+    #[arg(long = "session-file", value_name = "FILE")]
+    session_file: Option<PathBuf>,
+
+    /// Verbose diagnostics on stderr (repeat for more)
+    #[arg(short = 'v', long = "verbose", action = clap::ArgAction::Count)]
+    verbose: u8,
+
+    /// No diagnostics on stderr, only the fatal error if the run fails
+    #[arg(short = 'q', long = "quiet", conflicts_with = "verbose")]
+    quiet: bool,
+
+    /// Ignore the site's own linked feed and always build the feed from extraction
+    #[arg(long = "no-passthrough", alias = "prefer-extraction")]
+    no_passthrough: bool,
+
+    /// Unicode normalization for extracted text. nfkc (default) also folds ligatures,
+    /// full-width and circled forms ("ﬁ" -> "fi", "①" -> "1"); nfc only composes accents
+    #[arg(long = "unicode-form", value_enum, default_value_t = UnicodeForm::Nfkc)]
+    unicode_form: UnicodeForm,
+
+    /// Strip emoji and other pictographic symbols from titles and descriptions for readers
+    /// that render them as boxes; "space" replaces each run with a space instead
+    #[arg(long = "strip-symbols", alias = "ascii-fallback", value_enum, value_name = "MODE",
+          num_args = 0..=1, default_missing_value = "remove")]
+    strip_symbols: Option<SymbolMode>,
+
+    /// Do not follow <meta http-equiv="refresh"> redirects (by default short-delay ones are followed)
+    #[arg(long = "no-meta-refresh")]
+    no_meta_refresh: bool,
+
+    /// Print diagnostics about the extracted items (age of newest/oldest, undated count) to stderr
+    #[arg(long = "check")]
+    check: bool,
+
+    /// Query parameter that identifies an article (e.g. t for phpBB's viewtopic.php?t=123);
+    /// URLs carrying it are never treated as listings and the parameter survives canonicalization
+    #[arg(long = "article-query-param", value_name = "NAME")]
+    article_query_param: Vec<String>,
+
+    /// Minimum heuristic score for a link to be fetched as an article candidate
+    #[arg(long = "min-score", default_value_t = 1, allow_negative_numbers = true)]
+    min_score: i32,
+
+    /// Emit <dc:source> with the original (aggregator/redirecting) URL of items whose link was unwrapped
+    #[arg(long = "keep-original-link")]
+    keep_original_link: bool,
+
+    /// Send a HEAD request per candidate first and skip non-200 or non-HTML ones without downloading them
+    #[arg(long = "head-first")]
+    head_first: bool,
+
+    /// Seed the random choices (User-Agent rotation, delay jitter) so a crawl is reproducible
+    #[arg(long = "seed", value_name = "N")]
+    seed: Option<u64>,
+
+    /// Instead of relaying the site's linked feed verbatim, parse it leniently and re-serialize
+    /// its entries, so a malformed upstream feed comes out valid (and in any --format)
+    #[arg(long = "repair-feed")]
+    repair_feed: bool,
+
+    /// Record each article page's declared language and emit it as <dc:language> per item
+    #[arg(long = "per-item-language")]
+    per_item_language: bool,
+
+    /// Link items to the article's <link rel="alternate" hreflang> variant in this language
+    /// (e.g. "de" or "pt-BR") when it has one; otherwise the default page is kept
+    #[arg(long = "prefer-lang", value_name = "LANG", value_parser = non_empty_trimmed)]
+    prefer_lang: Option<String>,
+
+    /// Take article pages' JSON-LD as-is instead of filling its missing image, description and
+    /// dates from og:/meta tags
+    #[arg(long = "no-merge-metadata")]
+    no_merge_metadata: bool,
+
+    /// Indent JSON output (ndjson then holds one multi-line object per item)
+    #[arg(long = "json-pretty")]
+    json_pretty: bool,
+
+    /// Request pacing preset: gentle (long pauses, one retry), normal, or aggressive (no pauses,
+    /// for sites you trust). --delay-ms, --jitter-ms and --retries override single settings
+    #[arg(long = "politeness", value_enum, default_value_t = Politeness::Normal)]
+    politeness: Politeness,
+
+    /// Fixed pause before every request, in milliseconds (overrides --politeness)
+    #[arg(long = "delay-ms", value_name = "MS")]
+    delay_ms: Option<u64>,
+
+    /// Random extra pause of up to this many milliseconds per request (overrides --politeness)
+    #[arg(long = "jitter-ms", value_name = "MS")]
+    jitter_ms: Option<u64>,
+
+    /// Retries for failed article fetches on listing pages (overrides --politeness)
+    #[arg(long = "retries", value_name = "N")]
+    retries: Option<u32>,
+
+    /// Skip JSON-LD blocks larger than this many bytes (huge blocks are slow to parse and walk)
+    #[arg(long = "max-json-ld-bytes", default_value_t = 256 * 1024, value_name = "BYTES")]
+    max_json_ld_bytes: usize,
+
+    /// Also look for articles in JSON data islands (<script id="__NEXT_DATA__"> and --data-island)
+    #[arg(long = "use-data-islands")]
+    use_data_islands: bool,
+
+    /// id of a <script> element holding JSON page data, for --use-data-islands
+    #[arg(long = "data-island", value_name = "ID", default_value = "__NEXT_DATA__")]
+    data_island: Vec<String>,
+
+    /// Channel-level <category> for the generated feed (repeatable)
+    #[arg(long = "channel-category", value_name = "STR", value_parser = non_empty_trimmed)]
+    channel_category: Vec<String>,
+
+    /// Where the channel <image> logo comes from, in order of preference: jsonld (publisher or
+    /// Organization logo), icon (apple-touch-icon, then icon links), og (og:image)
+    #[arg(long = "feed-image-from", value_enum, value_name = "SOURCE", value_delimiter = ',',
+          default_values_t = [ImageSource::Jsonld, ImageSource::Icon, ImageSource::Og])]
+    feed_image_from: Vec<ImageSource>,
+
+    /// Only crawl same-host URLs under this path (e.g. /world/), both as candidates and from listings
+    #[arg(long = "path-prefix", value_name = "PREFIX", value_parser = path_prefix)]
+    path_prefix: Option<String>,
+
+    /// Channel <webMaster>: contact for technical issues, "EMAIL" or "EMAIL (Name)"
+    #[arg(long = "web-master", value_name = "EMAIL", value_parser = contact_email)]
+    web_master: Option<String>,
+
+    /// Channel <managingEditor>: contact for editorial content, "EMAIL" or "EMAIL (Name)"
+    #[arg(long = "managing-editor", value_name = "EMAIL", value_parser = contact_email)]
+    managing_editor: Option<String>,
+
+    /// Without --managing-editor, take it from the email of the start page's JSON-LD publisher or author
+    #[arg(long = "auto-contact")]
+    auto_contact: bool,
+
+    /// Fetch "read more" fragments (data-article-url, data-fragment, read-more API links) of
+    /// teaser pages and append their text to the description; best-effort, keeps the teaser on failure
+    #[arg(long = "read-more")]
+    read_more: bool,
+
+    /// Fetch each article's oEmbed JSON (<link type="application/json+oembed">) and prefer its
+    /// title and thumbnail, filling author and description from it; pages without one are unchanged
+    #[arg(long = "use-oembed")]
+    use_oembed: bool,
+
+    /// Wrap item descriptions in CDATA instead of XML-escaping them
+    #[arg(long = "cdata-descriptions")]
+    cdata_descriptions: bool,
+
+    /// Also drop plain spaces inside href values (line breaks and tabs from templated markup
+    /// are always removed); off by default because some sites do have spaces in file names
+    #[arg(long = "collapse-whitespace-in-links")]
+    collapse_whitespace_in_links: bool,
+
+    /// List per-item quality warnings (missing or unparseable dates, empty descriptions,
+    /// suspicious links) as XML comments at the top of the feed
+    #[arg(long = "validation-comments")]
+    validation_comments: bool,
+
+    /// Run the whole crawl but print fetch/timing statistics to stderr instead of writing a feed
+    #[arg(long = "dry-crawl")]
+    dry_crawl: bool,
+
+    /// Build items from the site's Google News sitemap (sitemap-news.xml, or one listed in
+    /// robots.txt); a plain sitemap supplies candidate URLs instead
+    #[arg(long = "follow-sitemap-news")]
+    follow_sitemap_news: bool,
+
+    /// Also write a standalone HTML page listing the items to FILE
+    #[arg(long = "html-index", value_name = "FILE")]
+    html_index: Option<PathBuf>,
+
+    /// Merge the new items into the RSS feed at FILE (created if missing) instead of printing a
+    /// feed: items already there keep their metadata, duplicates (by GUID) are dropped, and the
+    /// newest --merge-keep items are written back, so the feed accumulates across runs
+    #[arg(long = "merge-into", value_name = "FILE")]
+    merge_into: Option<PathBuf>,
+
+    /// Items kept in the --merge-into feed
+    #[arg(long = "merge-keep", value_name = "N", default_value_t = 100, requires = "merge_into")]
+    merge_keep: usize,
+
+    /// Send Accept/Accept-Language/Sec-Fetch-* headers matching the browser in the User-Agent
+    #[arg(long = "realistic-headers")]
+    realistic_headers: bool,
+
+    /// Podcast feed: declare the itunes namespace, use episode audio as the enclosure and emit
+    /// itunes:author/summary/image/duration/episode where the data exists
+    #[arg(long = "podcast")]
+    podcast: bool,
+
+    /// Character encoding of RSS output (e.g. windows-1252); characters it can't represent
+    /// become numeric character references
+    #[arg(long = "output-encoding", value_name = "CHARSET", value_parser = output_charset)]
+    output_encoding: Option<&'static Encoding>,
+
+    /// Append word count and estimated reading time to descriptions (JSON-LD wordCount,
+    /// else counted from the article text)
+    #[arg(long = "reading-time")]
+    reading_time: bool,
+
+    /// Last resort for items with no date at all: "now" stamps them with the crawl time, one
+    /// second apart in discovery order. These dates are synthetic, only there for stable sorting
+    #[arg(long = "fallback-date", value_enum, value_name = "WHEN")]
+    fallback_date: Option<FallbackDate>,
+
+    /// Reshape item titles, e.g. "{site}: {title}" (placeholders: {title}, {description},
+    /// {site}, {date}, {host}; "{{" and "}}" for literal braces)
+    #[arg(long = "title-template", value_name = "TEMPLATE", value_parser = item_template)]
+    title_template: Option<String>,
+
+    /// Reshape item descriptions, with the same placeholders as --title-template
+    #[arg(long = "description-template", value_name = "TEMPLATE", value_parser = item_template)]
+    description_template: Option<String>,
+
+    /// Keep at most N items (the newest) from any one host, before the overall --max-pages cap
+    #[arg(long = "max-items-per-host", value_name = "N")]
+    max_items_per_host: Option<usize>,
+
+    /// Most requests in flight to one host at a time; fetches beyond it wait for a slot.
+    /// Candidates are fetched one after another today, so this only bites once fetching is parallel
+    #[arg(long = "concurrency-per-host", value_name = "N", default_value_t = 2, value_parser = clap::value_parser!(u32).range(1..))]
+    concurrency_per_host: u32,
+
+    /// Idle connections kept open per host for reuse (0 disables pooling)
+    #[arg(long = "pool-size", value_name = "N", default_value_t = 4)]
+    pool_size: usize,
+
+    /// Seconds an idle pooled connection stays open for reuse
+    #[arg(long = "keep-alive-secs", value_name = "SECS", default_value_t = 90)]
+    keep_alive_secs: u64,
+
+    /// Seconds between TCP keep-alive probes on open connections (0 disables them)
+    #[arg(long = "tcp-keepalive-secs", value_name = "SECS", default_value_t = 60)]
+    tcp_keepalive_secs: u64,
+
+    /// Drop items that still have no image after every image fallback
+    #[arg(long = "require-image")]
+    require_image: bool,
+
+    /// Drop (with a warning) items whose title or description is still garbled after mojibake
+    /// repair: replacement characters or UTF-8-read-as-Windows-1252 sequences such as "Ã©"/"â€™"
+    #[arg(long = "strict-encoding")]
+    strict_encoding: bool,
+
+    /// CSS selector for cruft removed from fetched article HTML before its text is used
+    /// (repeatable; replaces the default list of scripts, iframes, ad slots, newsletter
+    /// sign-ups, promos, related-story and share widgets)
+    #[arg(long = "strip-selector", value_name = "CSS", value_parser = css_selector, default_values_t = DEFAULT_STRIP_SELECTORS.map(String::from))]
+    strip_selector: Vec<String>,
+
+    /// When a page has no meta/JSON-LD description, use its first substantive body paragraph
+    #[arg(long = "first-paragraph-as-description")]
+    first_paragraph_as_description: bool,
+
+    /// Per-host request timeout overriding --timeout-ms, as HOST=MS (repeatable; also
+    /// applies to subdomains of HOST)
+    #[arg(long = "host-timeout", value_name = "HOST=MS", value_parser = host_timeout_arg)]
+    host_timeout: Vec<(String, u64)>,
+
+    /// Size of the stdout buffer in bytes. Feeds and tables are flushed once at the end,
+    /// ndjson after every item
+    #[arg(long = "output-buffer", value_name = "BYTES", default_value_t = 64 * 1024)]
+    output_buffer: usize,
+
+    /// Print every field of one extracted item to stderr (then write the feed as usual)
+    #[arg(long = "preview")]
+    preview: bool,
+
+    /// Which item --preview shows, counting from 1
+    #[arg(long = "preview-index", value_name = "N", default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..), requires = "preview")]
+    preview_index: u32,
+
+    /// From <picture>/srcset candidates, prefer JPEG/PNG/GIF over AVIF and WebP for older readers
+    #[arg(long = "compatible-images")]
+    compatible_images: bool,
+
+    /// DANGEROUS: accept any TLS certificate, including self-signed, expired or wrong-host
+    /// ones. Anyone on the network path can then read and alter the traffic; prefer --ca-cert
+    #[arg(long = "allow-insecure-tls")]
+    allow_insecure_tls: bool,
+
+    /// Also trust this root CA (PEM or DER), e.g. an intranet's own certificate authority
+    #[arg(long = "ca-cert", value_name = "FILE")]
+    ca_cert: Option<PathBuf>,
+
+    /// Save crawl progress to FILE (default: a per-URL file in the state dir) and resume from
+    /// it on the next run; removed once a crawl completes
+    #[arg(long = "checkpoint", value_name = "FILE", num_args = 0..=1)]
+    checkpoint: Option<Option<PathBuf>>,
+
+    /// Trailing-slash policy for article links when comparing and deduplicating them: add,
+    /// remove, leave, or auto (follow the start page's majority convention)
+    #[arg(long = "normalize-links", value_enum, value_name = "POLICY", default_value_t = SlashPolicy::Leave)]
+    normalize_links: SlashPolicy,
+
+    /// Download budget for the whole crawl: once this many body bytes have been fetched, stop
+    /// fetching and write the items collected so far
+    #[arg(long = "max-total-bytes", value_name = "BYTES")]
+    max_total_bytes: Option<usize>,
+}
+
+const DEFAULT_STRIP_SELECTORS: [&str; 12] = [
+    "script", "style", "iframe", "ins", "noscript", "template",
+    "[class*=newsletter], [id*=newsletter]", "[class*=promo], [id*=promo]",
+    "[class*=related], [id*=related]", "[class*=social-share], [id*=social-share]",
+    "[class*=ad-slot], [id*=ad-slot]", "aside",
+];
+
+fn css_selector(s: &str) -> Result<String, String> {
+    Selector::parse(s).map_err(|e| format!("invalid selector: {}", e))?;
+    Ok(s.to_string())
+}
+
+fn host_timeout_arg(s: &str) -> Result<(String, u64), String> {
+    let (host, ms) = s.split_once('=').ok_or("expected HOST=MS")?;
+    let host = host.trim().trim_start_matches("www.").to_lowercase();
+    if host.is_empty() { return Err("missing host".to_string()); }
+    let ms = ms.trim().parse::<u64>().map_err(|_| format!("invalid timeout in milliseconds: {}", ms))?;
+    Ok((host, ms))
+}
+
+fn non_empty_trimmed(s: &str) -> Result<String, String> {
+    let t = s.trim();
+    if t.is_empty() { Err("must not be empty".to_string()) } else { Ok(t.to_string()) }
+}
+
+// Path prefix with a leading slash; "world" and "/world" both mean the /world section.
+fn path_prefix(s: &str) -> Result<String, String> {
+    let t = s.trim().trim_start_matches('/');
+    if t.is_empty() { return Err("must name a path below /".to_string()); }
+    Ok(format!("/{}", t))
+}
+
+static RE_CONTACT_EMAIL: Lazy<Regex> = Lazy::new(|| Regex::new(r"^[^@\s()]+@[^@\s()]+\.[^@\s().]+(?:\s+\([^()]+\))?$").unwrap());
+
+// RSS 2.0 contact: an address, optionally followed by the person's name in parentheses.
+fn contact_email(s: &str) -> Result<String, String> {
+    let t = RE_WHITESPACE.replace_all(s.trim(), " ").into_owned();
+    if RE_CONTACT_EMAIL.is_match(&t) { Ok(t) } else { Err(format!("expected EMAIL or \"EMAIL (Name)\", got {:?}", s)) }
+}
+
+const TEMPLATE_PLACEHOLDERS: &[&str] = &["title", "description", "site", "date", "host"];
+
+fn item_template(s: &str) -> Result<String, String> {
+    expand_template(s, |name| TEMPLATE_PLACEHOLDERS.contains(&name).then(String::new))?;
+    Ok(s.to_string())
+}
+
+// Substitute {name} placeholders; "{{"/"}}" are literal braces. Fails on an unknown or
+// unterminated placeholder.
+fn expand_template(tpl: &str, lookup: impl Fn(&str) -> Option<String>) -> Result<String, String> {
+    let mut out = String::new();
+    let mut chars = tpl.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => { chars.next(); out.push('{'); }
+            '}' if chars.peek() == Some(&'}') => { chars.next(); out.push('}'); }
+            '{' => {
+                let mut name = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(ch) => name.push(ch),
+                        None => return Err(format!("unterminated placeholder {{{}", name)),
+                    }
+                }
+                let value = lookup(name.trim())
+                    .ok_or_else(|| format!("unknown placeholder {{{}}} (expected one of: {})", name, TEMPLATE_PLACEHOLDERS.join(", ")))?;
+                out.push_str(&value);
+            }
+            '}' => return Err("unmatched '}' (write '}}' for a literal brace)".to_string()),
+            _ => out.push(c),
+        }
+    }
+    Ok(out)
+}
+
+// Any WHATWG label encoding_rs can also encode to (so not UTF-16 or "replacement").
+fn output_charset(s: &str) -> Result<&'static Encoding, String> {
+    match Encoding::for_label(s.trim().as_bytes()) {
+        Some(enc) if enc.output_encoding() == enc => Ok(enc),
+        Some(enc) => Err(format!("cannot write {} output", enc.name())),
+        None => Err(format!("unknown charset: {}", s)),
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+enum SlashPolicy {
+    Leave,
+    Add,
+    Remove,
+    Auto,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+enum Politeness {
+    Gentle,
+    Normal,
+    Aggressive,
+}
+
+// Pacing settings resolved from --politeness and the individual overrides.
+struct Pacing {
+    delay_ms: u64,
+    jitter_ms: u64,
+    retries: u32,
+}
+
+fn pacing() -> Pacing {
+    let args = opts();
+    // (delay, jitter, retries); normal is the long-standing 200-600ms pause with two retries
+    let (delay_ms, jitter_ms, retries) = match args.politeness {
+        Politeness::Gentle => (1500, 1500, 1),
+        Politeness::Normal => (200, 400, 2),
+        Politeness::Aggressive => (0, 0, 3),
+    };
+    Pacing {
+        delay_ms: args.delay_ms.unwrap_or(delay_ms),
+        jitter_ms: args.jitter_ms.unwrap_or(jitter_ms),
+        retries: args.retries.unwrap_or(retries),
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+enum FallbackDate {
+    Now,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+enum ImageSource {
+    Jsonld,
+    Og,
+    Icon,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+enum DedupKey {
+    Link,
+    Guid,
+    Title,
+    ContentHash,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+enum SymbolMode {
+    Remove,
+    Space,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+enum UnicodeForm {
+    Nfc,
+    Nfkc,
+    None,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+enum SummaryUnit {
+    Words,
+    Sentences,
+    Chars,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+enum OutputFormat {
+    Rss,
+    /// RSS 1.0 (RDF)
+    Rss10,
+    Table,
+    /// newline-delimited JSON, written as items are extracted
+    Ndjson,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+enum TitleCase {
+    Smart,
+    Lower,
+    None,
+}
+
+// Parsed command line, set once in main so deep helpers can consult options without threading them.
+static OPTIONS: OnceCell<Args> = OnceCell::new();
+
+fn opts() -> &'static Args {
+    OPTIONS.get().expect("options not initialised")
+}
+
+// Number of SIGINTs received; the first stops crawling, the second aborts.
+static INTERRUPTS: AtomicUsize = AtomicUsize::new(0);
+
+fn interrupted() -> bool {
+    INTERRUPTS.load(Ordering::SeqCst) > 0
+}
+
+// Crawl counters, reported by --dry-crawl.
+struct CrawlStats {
+    started: OnceCell<Instant>,
+    pages: AtomicUsize,
+    bytes: AtomicUsize,
+    failures: AtomicUsize,
+    // items removed by filter_items
+    dropped: AtomicUsize,
+}
+
+static STATS: CrawlStats = CrawlStats {
+    started: OnceCell::new(),
+    pages: AtomicUsize::new(0),
+    bytes: AtomicUsize::new(0),
+    failures: AtomicUsize::new(0),
+    dropped: AtomicUsize::new(0),
+};
+
+fn verbose() -> bool {
+    opts().verbose > 0
+}
+
+// stderr diagnostics, silenced by --quiet. Fatal errors and explicitly requested reports
+// (--dry-crawl, --check) use eprintln! directly.
+macro_rules! diag {
+    ($($arg:tt)*) => {
+        if !OPTIONS.get().is_some_and(|a| a.quiet) { eprintln!($($arg)*); }
+    };
 }
 
 fn main() {
-    let args = Args::parse();
+    let args = OPTIONS.get_or_init(Args::parse);
 
-    match run(&args) {
+    // First Ctrl-C: stop fetching and write what we have. Second: abort immediately.
+    let _ = ctrlc::set_handler(|| {
+        if INTERRUPTS.fetch_add(1, Ordering::SeqCst) == 0 {
+            diag!("Interrupted: writing items collected so far (press Ctrl-C again to abort)");
+        } else {
+            std::process::exit(130);
+        }
+    });
+
+    let result = run(args);
+    if verbose() {
+        diag!("Downloaded {} KB in total", STATS.bytes.load(Ordering::Relaxed) / 1024);
+    }
+    match result {
         Ok(()) => std::process::exit(0),
         Err(e) => {
             eprintln!("error: {}", e);
-            std::process::exit(4);
+            std::process::exit(exit_code(e.as_ref()));
+        }
+    }
+}
+
+// Failures callers may want to tell apart by exit code.
+#[derive(Debug)]
+enum RunError {
+    // extraction produced fewer than --min-items items
+    NoArticles { found: usize, required: usize },
+}
+
+impl std::fmt::Display for RunError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RunError::NoArticles { found: 0, .. } => write!(f, "no articles found"),
+            RunError::NoArticles { found, required } => write!(f, "only {} article(s) found, {} required", found, required),
         }
     }
 }
 
+impl Error for RunError {}
+
+// Exit codes: 3 when no (or too few) articles were found, 4 for any other failure.
+fn exit_code(e: &(dyn Error + 'static)) -> i32 {
+    match e.downcast_ref::<RunError>() {
+        Some(RunError::NoArticles { .. }) => 3,
+        None => 4,
+    }
+}
+
 fn run(args: &Args) -> Result<(), Box<dyn Error>> {
+    STATS.started.get_or_init(Instant::now);
+    if args.merge_into.is_some() && args.format != OutputFormat::Rss {
+        return Err("--merge-into maintains an RSS 2.0 file; use it with --format rss".into());
+    }
+    // a feed being merged into can't take another feed verbatim, so linked feeds are re-read
+    let repair_feed = args.repair_feed || args.merge_into.is_some();
+    // An explicit state dir is created up front so a bad path fails early
+    if args.state_dir.is_some() {
+        ensure_state_dir()?;
+    }
+    if let Some(path) = &args.host_auth_file {
+        let _ = HOST_AUTH.set(load_host_auth(path)?);
+    }
+    if let Some(path) = &args.session_file {
+        let start = Url::parse(&args.url)?;
+        let session = load_session(path, &start).map_err(|e| format!("invalid session file {}: {}", path.display(), e))?;
+        let _ = SESSION.set(session);
+    }
+
     let timeout = Duration::from_millis(args.timeout_ms);
-    let client = Client::builder()
+    let redirect = if args.no_redirects {
+        reqwest::redirect::Policy::none()
+    } else {
+        reqwest::redirect::Policy::limited(args.max_redirects)
+    };
+    // HTTP/2 is negotiated via ALPN where the server offers it, multiplexing every request to a
+    // host over one connection; HTTP/1.1 hosts reuse pooled keep-alive connections instead.
+    // Same-host crawls save a TCP+TLS handshake per page either way; -vv shows the protocol
+    // and time of each request and --dry-crawl the average per page, for comparing settings.
+    // Measured with --dry-crawl on 61 same-host pages over loopback HTTPS/1.1 (debug build):
+    // --pool-size 0 took 0.31s (5 ms/page), --pool-size 4 took 0.17s (3 ms/page); over plain
+    // HTTP the two were within noise. Real networks add a round trip or two per new connection.
+    // The pool timeout decides when an unused connection is dropped; TCP keep-alive probes only
+    // stop a NAT or firewall from silently killing one that is still pooled.
+    let tcp_keepalive = (args.tcp_keepalive_secs > 0).then(|| Duration::from_secs(args.tcp_keepalive_secs));
+    let mut builder = Client::builder()
         .timeout(timeout)
-        .redirect(reqwest::redirect::Policy::limited(10))
-        .build()?;
+        .redirect(redirect)
+        .pool_max_idle_per_host(args.pool_size)
+        .pool_idle_timeout(Duration::from_secs(args.keep_alive_secs))
+        .tcp_keepalive(tcp_keepalive)
+        .http2_adaptive_window(true);
+    if let Some(path) = &args.ca_cert {
+        for cert in load_ca_certs(path)? {
+            builder = builder.add_root_certificate(cert);
+        }
+    }
+    if args.allow_insecure_tls {
+        diag!("warning: --allow-insecure-tls: TLS certificates are NOT verified");
+        builder = builder.danger_accept_invalid_certs(true);
+    }
+    let client = builder.build()?;
 
     let start_url = Url::parse(&args.url)?;
 
-    // Fetch the page (with rotating UA, standard headers and modest delay)
-    let body = get_text_with_headers(&client, &start_url, args.timeout_ms)?;
+    // Fetch the page (with rotating UA, standard headers and modest delay).
+    // Relative links resolve against where we ended up after redirects.
+    let http = HttpFetcher::new(&client, args.timeout_ms);
+    let host_limit = HostLimitFetcher::new(&http, args.concurrency_per_host);
+    let budget = BudgetFetcher::new(&host_limit, args.max_total_bytes);
+    let visited = VisitedFetcher::new(&budget);
+    let fetcher = MetaRefreshFetcher { inner: &visited };
+    let start_page = fetcher.get(&start_url)?;
+
+    // 0) the start URL is itself a feed: pass it through, or read it into our model with
+    //    --repair-feed (and for non-RSS output formats, which can't take it verbatim)
+    if start_page.is_feed() {
+        if args.format == OutputFormat::Rss && !repair_feed && !args.dry_crawl {
+            return emit_document(start_page.body.as_bytes());
+        }
+        let mut items = feed_source_items(&start_page.body, &start_page.url, &start_page.url, ItemFilter::from_opts());
+        if args.dedupe_similar { dedupe_similar(&mut items, args.similarity_threshold); }
+        items.truncate(args.max_pages);
+        if items.is_empty() || items.len() < args.min_items {
+            return Err(RunError::NoArticles { found: items.len(), required: args.min_items.max(1) }.into());
+        }
+        finalize_items(&fetcher, &mut items);
+        return write_output(&start_page.url, &Channel::default(), &items);
+    }
+
+    let Page { url: start_url, body, .. } = start_page;
     let document = Html::parse_document(&body);
+    if args.normalize_links == SlashPolicy::Auto {
+        let convention = slash_convention(&document, &start_url);
+        if verbose() { diag!("Link convention: {:?} trailing slash", convention); }
+        let _ = LINK_SLASH.set(convention);
+    }
 
     // If the start page appears to be paywalled, bail out — unless domain is allowed
     if !allowed_domain(&start_url) && is_paywalled_page(&document) {
-        eprintln!("Skipping start URL (paywalled): {}", start_url.as_str());
+        diag!("Skipping start URL (paywalled): {}", start_url.as_str());
         return Err("start page appears to be paywalled".into());
     }
 
-    // 1) detect linked RSS/Atom
-    if let Some(feed_url) = find_linked_feed(&document, &start_url) {
-        // Try to fetch the feed using the same helper (benefits from headers and delay)
-        if let Ok(feed_text) = get_text_with_headers(&client, &feed_url, args.timeout_ms) {
-                io::stdout().write_all(feed_text.as_bytes())?;
-                io::stdout().write_all(b"\n")?;
-                io::stdout().flush()?;
+    // 1) detect linked RSS/Atom (passed through verbatim, so only for RSS output and unless disabled;
+    //    --repair-feed re-serializes its entries instead)
+    let passthrough = args.format == OutputFormat::Rss && !args.no_passthrough && !args.dry_crawl;
+    if passthrough || repair_feed {
+        // Fetch the feed using the same helper (benefits from headers and delay)
+        if let Some(Page { url: feed_url, body: feed_text, .. }) = fetch_linked_feed(&fetcher, &document, &start_url) {
+            if repair_feed {
+                let mut items = feed_source_items(&feed_text, &feed_url, &start_url, ItemFilter::from_opts());
+                if args.dedupe_similar { dedupe_similar(&mut items, args.similarity_threshold); }
+                items.truncate(args.max_pages);
+                if !items.is_empty() && items.len() >= args.min_items {
+                    finalize_items(&fetcher, &mut items);
+                    let channel = channel_metadata(&document, &start_url, &items);
+                    write_output(&start_url, &channel, &items)?;
+                    return Ok(());
+                }
+                diag!("Linked feed {} yielded no usable entries; extracting from the page", feed_url);
+            } else {
+                return emit_document(feed_text.as_bytes());
+            }
+        }
+    }
+
+    // 1b) opt-in: the news sitemap carries clean titles and dates without fetching any article
+    if args.follow_sitemap_news {
+        let mut items = items_from_news_sitemap(&fetcher, &start_url, args.max_pages);
+        filter_items(&start_url, &mut items);
+        sort_newest_first(&mut items);
+        items.truncate(args.max_pages);
+        if !items.is_empty() && items.len() >= args.min_items {
+            finalize_items(&fetcher, &mut items);
+            let channel = channel_metadata(&document, &start_url, &items);
+            write_output(&start_url, &channel, &items)?;
             return Ok(());
         }
     }
 
     // 2) try JSON-LD
-    if let Some(items) = extract_from_json_ld(&document, &start_url) {
-        // Filter out listing, blacklisted or error pages returned by JSON-LD
-        let filtered: Vec<Item> = items.into_iter().filter(|it| {
-            if is_error_page(&document, &it.title, &it.description) { return false; }
-            if let Ok(u) = Url::parse(&it.link) {
-                return !is_blacklisted_url(&u) && !is_listing_page(&u, &start_url);
-            }
-            true
-        }).collect();
-        if !filtered.is_empty() {
-            write_rss(&start_url, &filtered)?;
+    let mut structured_found = 0;
+    if let Some(mut filtered) = start_page_json_ld_items(&document, &start_url, ItemFilter::from_opts()) {
+        if args.dedupe_similar { dedupe_similar(&mut filtered, args.similarity_threshold); }
+        // same cap as the HTML path; sort first so a large @graph keeps its newest articles
+        sort_newest_first(&mut filtered);
+        filtered.truncate(args.max_pages);
+        structured_found = filtered.len();
+        if !filtered.is_empty() && filtered.len() >= args.min_items {
+            finalize_items(&fetcher, &mut filtered);
+            let channel = channel_metadata(&document, &start_url, &filtered);
+            write_output(&start_url, &channel, &filtered)?;
+            return Ok(());
+        }
+        // otherwise (none, or fewer than --min-items) fall through to HTML extraction
+    }
+
+    // 2b) opt-in: JSON data islands of client-rendered sites
+    if args.use_data_islands {
+        let mut items = extract_from_data_islands(&document, &start_url);
+        filter_items(&start_url, &mut items);
+        sort_newest_first(&mut items);
+        items.truncate(args.max_pages);
+        structured_found = structured_found.max(items.len());
+        if !items.is_empty() && items.len() >= args.min_items {
+            finalize_items(&fetcher, &mut items);
+            let channel = channel_metadata(&document, &start_url, &items);
+            write_output(&start_url, &channel, &items)?;
             return Ok(());
         }
-        // otherwise fall through to HTML extraction
+    }
+
+    if args.structured_only {
+        return Err(RunError::NoArticles { found: structured_found, required: args.min_items.max(1) }.into());
     }
 
     // 3) fallback: extract article-like elements and optionally fetch candidate pages
-    let items = extract_from_html(&client, &document, &start_url, args.max_pages, args.timeout_ms);
-    if items.is_empty() {
-        return Err("no articles found".into());
+    if let Some(path) = &args.checkpoint {
+        let path = match path {
+            Some(p) => p.clone(),
+            None => ensure_state_dir()?.join(format!("checkpoint-{:016x}.json", fnv1a64(args.url.as_bytes()))),
+        };
+        start_checkpoint(path, &args.url, &visited);
+    }
+    if args.format == OutputFormat::Ndjson {
+        let _ = STREAM_CHANNEL.set(channel_metadata(&document, &start_url, &[]));
+    }
+    let mut items = extract_from_html(&fetcher, &document, &start_url, args.max_pages);
+    if items.is_empty() || items.len() < args.min_items {
+        return Err(RunError::NoArticles { found: items.len(), required: args.min_items.max(1) }.into());
     }
+    finalize_items(&fetcher, &mut items);
 
-    write_rss(&start_url, &items)?;
+    let channel = channel_metadata(&document, &start_url, &items);
+    write_output(&start_url, &channel, &items)?;
+    // an interrupted crawl keeps its checkpoint for the next run
+    if !interrupted() { finish_checkpoint(); }
     Ok(())
 }
 
-#[derive(Debug)]
+// Last pass over the surviving items before serialization (shared by the JSON-LD and HTML paths).
+fn finalize_items(fetcher: &dyn Fetcher, items: &mut [Item]) {
+    let args = opts();
+    shape_items(items);
+    if args.probe_enclosures { probe_enclosures(fetcher, items); }
+    if args.check || verbose() { report_item_ages(items); }
+    // after the age report, which should count only real dates
+    if args.fallback_date == Some(FallbackDate::Now) { stamp_undated(items, chrono::Utc::now()); }
+}
+
+// The per-item text passes of finalize_items, also applied to items streamed mid-crawl.
+fn shape_items(items: &mut [Item]) {
+    let args = opts();
+    if let Some(n) = args.summary_length {
+        for it in items.iter_mut() {
+            if let Some(d) = &it.description { it.description = Some(summarize(d, n, args.summary_unit)); }
+        }
+    }
+    if args.reading_time {
+        for it in items.iter_mut() {
+            let Some(words) = it.word_count else { continue };
+            let note = reading_time_note(words);
+            it.description = Some(match it.description.take() {
+                Some(d) if !d.trim().is_empty() => format!("{} ({})", d.trim_end(), note),
+                _ => note,
+            });
+        }
+    }
+}
+
+// --fallback-date now: synthetic dates for undated items, a second apart counting back from
+// `now`, so newest-first readers list them in the order they were discovered.
+fn stamp_undated(items: &mut [Item], now: DateTime<chrono::Utc>) {
+    let undated = items.iter_mut().filter(|it| it.pub_date.as_deref().and_then(parse_pub_date).is_none());
+    for (i, it) in undated.enumerate() {
+        let when = now - chrono::Duration::seconds(i as i64);
+        it.pub_date = Some(when.to_rfc3339_opts(chrono::SecondsFormat::Secs, true));
+    }
+}
+
+// Average adult silent reading speed.
+const WORDS_PER_MINUTE: u64 = 230;
+
+fn reading_time_note(words: u64) -> String {
+    let minutes = words.div_ceil(WORDS_PER_MINUTE).max(1);
+    format!("{} words, {} min read", words, minutes)
+}
+
+// Paragraphs shorter than this are bylines, datelines, "Advertisement" labels and the like.
+const MIN_LEAD_PARAGRAPH_CHARS: usize = 80;
+
+// First substantive paragraph of the article body (<article>, then the main content block,
+// then anywhere), skipping captions and anything in a --strip-selector region.
+fn first_paragraph(doc: &Html) -> Option<String> {
+    let stripped: HashSet<_> = opts().strip_selector.iter().map(String::as_str)
+        .chain(["figure, figcaption, header, footer, nav"])
+        .filter_map(|css| Selector::parse(css).ok())
+        .flat_map(|sel| doc.select(&sel).map(|el| el.id()).collect::<Vec<_>>())
+        .collect();
+    ["article p", "[itemprop=articleBody] p", "main p", "p"].iter().find_map(|css| {
+        let sel = Selector::parse(css).ok()?;
+        doc.select(&sel)
+            .filter(|p| !p.ancestors().any(|a| stripped.contains(&a.id())))
+            .map(|p| fix_mojibake(&p.text().flat_map(str::split_whitespace).collect::<Vec<_>>().join(" ")))
+            .find(|text| text.chars().count() >= MIN_LEAD_PARAGRAPH_CHARS)
+    })
+}
+
+// (url, size) candidates of a srcset: "a.jpg 800w, b.jpg 1600w" or "a.jpg 1x, b.jpg 2x".
+// Width descriptors are pixels; density ones are scaled so a 2x beats a typical 1x width.
+fn srcset_candidates(srcset: &str) -> Vec<(String, u32)> {
+    srcset.split(',')
+        .filter_map(|cand| {
+            let mut parts = cand.split_whitespace();
+            let url = parts.next()?.to_string();
+            let size = match parts.next() {
+                Some(d) if d.ends_with('w') => d.trim_end_matches('w').parse().ok()?,
+                Some(d) if d.ends_with('x') => (d.trim_end_matches('x').parse::<f32>().ok()? * 1000.0) as u32,
+                _ => 1,
+            };
+            Some((url, size))
+        })
+        .collect()
+}
+
+fn is_modern_image_format(url: &str, declared: Option<&str>) -> bool {
+    let lower = url.to_lowercase();
+    declared.is_some_and(|t| t.contains("avif") || t.contains("webp"))
+        || [".avif", ".webp", "format=webp", "format=avif", "fm=webp", "fm=avif"].iter().any(|m| lower.contains(m))
+}
+
+// Best URL for an <img>: the largest candidate among its srcset, its src and, inside a
+// <picture>, the sibling <source srcset>s. `compatible` passes over AVIF/WebP when another
+// format is on offer.
+fn best_image_source(img: ElementRef, base: &Url, compatible: bool) -> Option<String> {
+    let mut candidates: Vec<(String, u32, bool)> = Vec::new();
+    let picture = img.parent().and_then(ElementRef::wrap).filter(|p| p.value().name() == "picture");
+    if let (Some(pic), Ok(sel_source)) = (picture, Selector::parse("source[srcset]")) {
+        for source in pic.select(&sel_source) {
+            let declared = source.value().attr("type");
+            for (url, size) in srcset_candidates(source.value().attr("srcset").unwrap_or_default()) {
+                let modern = is_modern_image_format(&url, declared);
+                candidates.push((url, size, modern));
+            }
+        }
+    }
+    for (url, size) in srcset_candidates(img.value().attr("srcset").unwrap_or_default()) {
+        let modern = is_modern_image_format(&url, None);
+        candidates.push((url, size, modern));
+    }
+    if let Some(src) = img.value().attr("src").filter(|s| !s.trim().is_empty() && !s.starts_with("data:")) {
+        candidates.push((src.to_string(), 0, is_modern_image_format(src, None)));
+    }
+    if compatible && candidates.iter().any(|(_, _, modern)| !modern) {
+        candidates.retain(|(_, _, modern)| !modern);
+    }
+    // first of the largest, so document order breaks ties
+    let best = candidates.iter().rev().max_by_key(|(_, size, _)| *size)?;
+    normalize_maybe_url(base, &best.0)
+}
+
+// Caption and credit for `image` from the <figcaption> of the first <figure> showing it. The
+// credit element's text is left out of the caption so readers don't show it twice.
+fn figure_caption(doc: &Html, base: &Url, image: &str) -> (Option<String>, Option<String>) {
+    let (Ok(sel_fig), Ok(sel_img), Ok(sel_cap), Ok(sel_credit)) = (
+        Selector::parse("figure"),
+        Selector::parse("img"),
+        Selector::parse("figcaption"),
+        Selector::parse("cite, small, .credit, [class*=credit]"),
+    ) else { return (None, None) };
+    let Some(cap) = doc.select(&sel_fig)
+        .find(|fig| fig.select(&sel_img).any(|img| best_image_source(img, base, opts().compatible_images).as_deref() == Some(image)))
+        .and_then(|fig| fig.select(&sel_cap).next())
+    else { return (None, None) };
+    let credit_node = cap.select(&sel_credit).next();
+    let caption: String = cap.descendants()
+        .filter(|n| credit_node.is_none_or(|c| !n.ancestors().any(|a| a.id() == c.id())))
+        .filter_map(|n| n.value().as_text().map(|t| t.to_string()))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let nonempty = |s: String| Some(fix_mojibake(&s)).filter(|s| !s.is_empty());
+    (nonempty(caption), credit_node.and_then(|c| nonempty(c.text().collect::<Vec<_>>().join(" "))))
+}
+
+// Words in the article body: paragraphs under <article>, else <main>, else the whole page.
+fn page_word_count(doc: &Html) -> Option<u64> {
+    ["article p", "main p", "p"].iter().find_map(|css| {
+        let sel = Selector::parse(css).ok()?;
+        let n = doc.select(&sel)
+            .map(|p| p.text().map(|t| t.split_whitespace().count() as u64).sum::<u64>())
+            .sum::<u64>();
+        (n > 0).then_some(n)
+    })
+}
+
+// Compact age for diagnostics: 45s, 12m, 5h, 3d.
+fn format_age(secs: i64) -> String {
+    let (sign, secs) = if secs < 0 { ("-", -secs) } else { ("", secs) };
+    match secs {
+        s if s < 60 => format!("{}{}s", sign, s),
+        s if s < 3600 => format!("{}{}m", sign, s / 60),
+        s if s < 86400 => format!("{}{}h", sign, s / 3600),
+        s => format!("{}{}d", sign, s / 86400),
+    }
+}
+
+// Age spread of the items relative to now. Many undated items usually means date extraction
+// is failing for the site; a large minimum age means the feed (or the site) has gone stale.
+fn report_item_ages(items: &[Item]) {
+    let now = chrono::Utc::now();
+    let mut ages: Vec<i64> = items.iter()
+        .filter_map(|it| it.pub_date.as_deref().and_then(parse_pub_date))
+        .map(|dt| (now - dt.with_timezone(&chrono::Utc)).num_seconds())
+        .collect();
+    let undated = items.len() - ages.len();
+    if ages.is_empty() {
+        eprintln!("Item ages: no parseable dates ({} undated)", undated);
+        return;
+    }
+    ages.sort_unstable();
+    eprintln!("Item ages: newest {}, median {}, oldest {} ({} dated, {} undated)",
+        format_age(ages[0]), format_age(ages[ages.len() / 2]), format_age(ages[ages.len() - 1]), ages.len(), undated);
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
 struct Item {
     title: String,
     link: String,
     description: Option<String>,
     pub_date: Option<String>,
+    // last modification (dateModified / article:modified_time), falling back to pub_date
+    updated: Option<String>,
     image: Option<String>,
+    // size of the primary image in bytes (JSON-LD contentSize or HEAD probe)
+    image_length: Option<u64>,
+    // all captured images (primary first, then source order, no duplicates), capped at --max-images
+    images: Vec<String>,
+    // the URL the link was found as, when unwrapping (?url=...) or a redirect changed it
+    original_link: Option<String>,
+    // GUID given by the source, used as is: live-blog updates are anchors (page#post-12) that
+    // link canonicalization would otherwise collapse into one item
+    guid: Option<String>,
+    // language declared by the article page (--per-item-language), e.g. "fr" or "en-GB"
+    language: Option<String>,
+    // podcast episode audio (JSON-LD AudioObject): URL, MIME type, size in bytes
+    audio: Option<String>,
+    audio_type: Option<String>,
+    audio_length: Option<u64>,
+    // episode running time as given (ISO 8601 "PT1H2M", seconds or "hh:mm:ss") and number
+    duration: Option<String>,
+    episode: Option<u64>,
+    // <figcaption> of the <figure> holding the primary image: caption text, and the photo
+    // credit when the caption marks one up (<cite>, <small>, .credit)
+    image_caption: Option<String>,
+    image_credit: Option<String>,
+    // Twitter/X card player: the embeddable player page, and its raw video stream with MIME type
+    // and size in bytes (--probe-enclosures)
+    player: Option<String>,
+    video: Option<String>,
+    video_type: Option<String>,
+    video_length: Option<u64>,
+    // words in the article body (JSON-LD wordCount, or counted with --reading-time)
+    word_count: Option<u64>,
+    // sections/topics: JSON-LD articleSection and keywords, news_keywords/article:tag metas, else the
+    // page's breadcrumb trail. Kept in source order (no set) so repeated runs emit identical feeds
+    categories: Vec<String>,
+    // JSON-LD author/publisher names and their images (avatar, logo)
+    author: Option<String>,
+    author_image: Option<String>,
+    publisher: Option<String>,
+    publisher_logo: Option<String>,
+}
+
+// The page's linked feed, if it fetches and really is one: a misconfigured alternate link
+// serving HTML falls back to extraction rather than being emitted as a broken feed.
+fn fetch_linked_feed(fetcher: &dyn Fetcher, document: &Html, base: &Url) -> Option<Page> {
+    let feed_url = find_linked_feed(document, base)?;
+    let page = fetcher.get(&feed_url).ok()?;
+    if !page.is_feed() {
+        diag!("Linked feed {} is not a feed ({}); extracting from the page",
+            page.url, page.content_type.as_deref().unwrap_or("no content type"));
+        return None;
+    }
+    Some(page)
 }
 
 fn find_linked_feed(document: &Html, base: &Url) -> Option<Url> {
@@ -121,7 +1150,7 @@ fn find_linked_feed(document: &Html, base: &Url) -> Option<Url> {
         if let Some(t) = node.value().attr("type") {
             if t.contains("rss") || t.contains("atom") {
                 if let Some(href) = node.value().attr("href") {
-                    if let Ok(u) = base.join(href) {
+                    if let Ok(u) = base.join(&clean_href(href)) {
                         return Some(u);
                     }
                 }
@@ -131,12 +1160,342 @@ fn find_linked_feed(document: &Html, base: &Url) -> Option<Url> {
     None
 }
 
+// One <url> of a sitemap, with the Google News extension fields when present.
+#[derive(Debug, Default, PartialEq)]
+struct SitemapEntry {
+    loc: String,
+    lastmod: Option<String>,
+    news_title: Option<String>,
+    news_date: Option<String>,
+}
+
+// Entries of a <urlset>, plus the child sitemap URLs of a <sitemapindex>. Elements are matched
+// by local name, so the news: prefix (or any other) doesn't matter; image:loc is ignored.
+fn parse_sitemap(text: &str) -> (Vec<SitemapEntry>, Vec<String>) {
+    let mut reader = quick_xml::Reader::from_str(text);
+    reader.config_mut().trim_text(true);
+    let mut entries = Vec::new();
+    let mut children = Vec::new();
+    let mut path: Vec<String> = Vec::new();
+    let mut entry = SitemapEntry::default();
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) => path.push(String::from_utf8_lossy(e.local_name().as_ref()).to_lowercase()),
+            Ok(Event::End(_)) => {
+                let closed = path.pop();
+                if closed.as_deref() == Some("url") && !entry.loc.is_empty() {
+                    entries.push(std::mem::take(&mut entry));
+                }
+            }
+            Ok(ev @ (Event::Text(_) | Event::CData(_))) => {
+                let value = match ev {
+                    Event::Text(t) => t.unescape().map(|c| c.into_owned()).unwrap_or_default(),
+                    Event::CData(c) => String::from_utf8_lossy(&c).into_owned(),
+                    _ => unreachable!(),
+                };
+                let value = value.trim().to_string();
+                let parent = path.len().checked_sub(2).map(|i| path[i].as_str());
+                match (parent, path.last().map(String::as_str)) {
+                    (Some("url"), Some("loc")) => entry.loc = value,
+                    (Some("url"), Some("lastmod")) => entry.lastmod = Some(value),
+                    (Some("news"), Some("title")) => entry.news_title = Some(value),
+                    (Some("news"), Some("publication_date")) => entry.news_date = Some(value),
+                    (Some("sitemap"), Some("loc")) => children.push(value),
+                    _ => {}
+                }
+            }
+            Ok(Event::Eof) => break,
+            Err(e) => {
+                diag!("Sitemap parse stopped at byte {}: {}", reader.buffer_position(), e);
+                break;
+            }
+            _ => {}
+        }
+    }
+    (entries, children)
+}
+
+// Sitemaps to try: Sitemap: lines in robots.txt that look news-specific, then the usual paths.
+fn news_sitemap_urls(fetcher: &dyn Fetcher, start: &Url) -> Vec<Url> {
+    let mut urls = Vec::new();
+    if let Ok(robots) = start.join("/robots.txt").map(|u| fetcher.get(&u)) {
+        for line in robots.map(|p| p.body).unwrap_or_default().lines() {
+            let Some((key, value)) = line.split_once(':') else { continue };
+            if key.trim().eq_ignore_ascii_case("sitemap") && value.to_lowercase().contains("news") {
+                urls.extend(Url::parse(value.trim()).ok());
+            }
+        }
+    }
+    for path in ["/sitemap-news.xml", "/news-sitemap.xml", "/sitemap_news.xml"] {
+        urls.extend(start.join(path).ok().filter(|u| !urls.contains(u)));
+    }
+    urls
+}
+
+// --follow-sitemap-news: items straight from news:title/news:publication_date. A sitemap without
+// the news extension only lists URLs, so those are fetched as ordinary article candidates.
+fn items_from_news_sitemap(fetcher: &dyn Fetcher, start: &Url, max_pages: usize) -> Vec<Item> {
+    for sitemap in news_sitemap_urls(fetcher, start) {
+        let Ok(page) = fetcher.get(&sitemap) else { continue };
+        let (mut entries, children) = parse_sitemap(&page.body);
+        // a sitemap index: read the (news-looking first) child sitemaps
+        let mut children: Vec<&String> = children.iter().collect();
+        children.sort_by_key(|c| !c.to_lowercase().contains("news"));
+        for child in children.into_iter().take(3) {
+            if let Ok(child_page) = Url::parse(child).map(|u| fetcher.get(&u)) {
+                entries.extend(child_page.map(|p| parse_sitemap(&p.body).0).unwrap_or_default());
+            }
+        }
+        if entries.is_empty() { continue; }
+        if verbose() { diag!("Using sitemap {} ({} entries)", sitemap, entries.len()); }
+        return sitemap_entries_to_items(fetcher, entries, start, max_pages);
+    }
+    Vec::new()
+}
+
+fn sitemap_entries_to_items(fetcher: &dyn Fetcher, entries: Vec<SitemapEntry>, base: &Url, max_pages: usize) -> Vec<Item> {
+    if entries.iter().any(|e| e.news_title.is_some()) {
+        return entries.into_iter()
+            .filter_map(|e| {
+                let title = clean_title(e.news_title.as_deref()?);
+                let pub_date = e.news_date.or(e.lastmod);
+                Some(Item { title, link: e.loc, updated: pub_date.clone(), pub_date, ..Default::default() })
+            })
+            .collect();
+    }
+    // generic sitemap: newest URLs first, then the usual per-article extraction
+    let mut entries = entries;
+    entries.sort_by_key(|e| std::cmp::Reverse(e.lastmod.as_deref().and_then(parse_pub_date)));
+    let candidates: Vec<Url> = entries.iter()
+        .filter_map(|e| Url::parse(&e.loc).ok())
+        .filter(|u| u.domain() == base.domain())
+        .take(max_pages)
+        .collect();
+    let mut items = Vec::new();
+    fetch_candidates(fetcher, &candidates, base, max_pages, &mut items);
+    items
+}
+
+// Lenient RSS/Atom reader for --repair-feed: tolerates mismatched end tags, HTML entities and
+// stray ampersands, treats markup inside fields as text, and keeps the entries read before an
+// unrecoverable error rather than failing the whole feed.
+fn parse_feed_lenient(text: &str, base: &Url) -> Vec<Item> {
+    let mut reader = quick_xml::Reader::from_str(text);
+    reader.config_mut().trim_text(true);
+    reader.config_mut().check_end_names = false;
+
+    let mut items = Vec::new();
+    let mut current: Option<Item> = None;
+    // element whose text is being collected (nested markup is folded into it)
+    let mut field: Option<String> = None;
+    let mut buf = String::new();
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) => {
+                let name = String::from_utf8_lossy(e.local_name().as_ref()).to_lowercase();
+                if name == "item" || name == "entry" {
+                    current = Some(Item::default());
+                    field = None;
+                } else if let Some(it) = current.as_mut() {
+                    feed_item_attrs(it, &name, &e, base);
+                    if field.is_none() {
+                        field = Some(name);
+                        buf.clear();
+                    }
+                }
+            }
+            Ok(Event::Empty(e)) => {
+                if let Some(it) = current.as_mut() {
+                    let name = String::from_utf8_lossy(e.local_name().as_ref()).to_lowercase();
+                    feed_item_attrs(it, &name, &e, base);
+                }
+            }
+            Ok(Event::Text(e)) if field.is_some() => {
+                match e.unescape() {
+                    Ok(t) => buf.push_str(&t),
+                    // HTML entities (&nbsp;) or a bare '&' upset the XML unescaper
+                    Err(_) => buf.push_str(&decode_html_entities(&String::from_utf8_lossy(&e))),
+                }
+                buf.push(' ');
+            }
+            Ok(Event::CData(e)) if field.is_some() => {
+                buf.push_str(&String::from_utf8_lossy(&e));
+                buf.push(' ');
+            }
+            Ok(Event::End(e)) => {
+                let name = String::from_utf8_lossy(e.local_name().as_ref()).to_lowercase();
+                if name == "item" || name == "entry" {
+                    items.extend(current.take().and_then(finish_feed_item));
+                    field = None;
+                } else if field.as_deref() == Some(name.as_str()) {
+                    if let Some(it) = current.as_mut() { set_feed_field(it, &name, buf.trim(), base); }
+                    field = None;
+                }
+            }
+            Ok(Event::Eof) => break,
+            Err(e) => {
+                diag!("Feed parse stopped at byte {}: {}", reader.buffer_position(), e);
+                break;
+            }
+            _ => {}
+        }
+    }
+    // an entry cut off by truncation or a parse error is still worth keeping
+    if let (Some(it), Some(name)) = (current.as_mut(), field) { set_feed_field(it, &name, buf.trim(), base); }
+    items.extend(current.and_then(finish_feed_item));
+    items
+}
+
+// Attribute-borne entry data: Atom <link href>, RSS <enclosure>, Media RSS images.
+fn feed_item_attrs(it: &mut Item, name: &str, e: &BytesStart, base: &Url) {
+    let attr = |key: &str| e.attributes().flatten()
+        .find(|a| a.key.local_name().as_ref() == key.as_bytes())
+        .and_then(|a| a.unescape_value().ok().map(|v| v.into_owned()));
+    match name {
+        "link" => {
+            let rel = attr("rel").unwrap_or_else(|| "alternate".to_string());
+            if let (true, Some(href)) = (rel == "alternate" && it.link.is_empty(), attr("href")) {
+                if let Ok(u) = base.join(href.trim()) { it.link = u.to_string(); }
+            }
+        }
+        "enclosure" | "content" | "thumbnail" if it.image.is_none() => {
+            let is_image = attr("type").is_some_and(|t| t.starts_with("image/"))
+                || attr("medium").is_some_and(|m| m == "image")
+                || name == "thumbnail"
+                // our own feeds (--merge-into) carry the item image as an untyped enclosure
+                || (name == "enclosure" && attr("type").is_none());
+            if let (true, Some(src)) = (is_image, attr("url")) {
+                it.image = normalize_maybe_url(base, &src);
+                it.images.extend(it.image.clone());
+            }
+        }
+        _ => {}
+    }
+}
+
+// Text-borne entry data. Descriptions are reduced to plain text like extracted ones.
+fn set_feed_field(it: &mut Item, name: &str, text: &str, base: &Url) {
+    if text.is_empty() { return; }
+    let plain = || fix_mojibake(&Html::parse_fragment(text).root_element().text().collect::<Vec<_>>().join(" "));
+    match name {
+        "title" => it.title = clean_title(&plain()),
+        "link" | "guid" => {
+            // the guid is kept as the entry's id, so a merge matches anchored and hashed ones
+            if name == "guid" { it.guid = Some(text.trim().to_string()); }
+            if !it.link.is_empty() { return; }
+            if let Ok(u) = base.join(text) {
+                if u.scheme() == "http" || u.scheme() == "https" { it.link = u.to_string(); }
+            }
+        }
+        "description" | "summary" => it.description = Some(plain()),
+        "encoded" | "content" if it.description.is_none() => it.description = Some(plain()),
+        "pubdate" | "published" | "date" | "issued" if it.pub_date.is_none() => it.pub_date = Some(text.to_string()),
+        "updated" | "modified" => it.updated = Some(text.to_string()),
+        "category" => push_categories(&mut it.categories, [plain()]),
+        "creator" | "author" if it.author.is_none() => it.author = Some(plain()),
+        _ => {}
+    }
+}
+
+fn finish_feed_item(mut it: Item) -> Option<Item> {
+    if it.title.is_empty() && it.link.is_empty() { return None; }
+    if it.pub_date.is_none() { it.pub_date = it.updated.clone(); }
+    if it.updated.is_none() { it.updated = it.pub_date.clone(); }
+    Some(it)
+}
+
+// Field names tried, in order, when reading an article out of arbitrary page JSON.
+const ISLAND_TITLE_KEYS: &[&str] = &["title", "headline", "name"];
+const ISLAND_URL_KEYS: &[&str] = &["url", "href", "link", "permalink", "canonicalUrl", "canonical_url", "path"];
+const ISLAND_DATE_KEYS: &[&str] = &["datePublished", "publishedAt", "published_at", "publishDate", "pubDate", "date", "createdAt", "created_at"];
+const ISLAND_DESC_KEYS: &[&str] = &["description", "excerpt", "summary", "dek", "subtitle", "teaser"];
+const ISLAND_IMAGE_KEYS: &[&str] = &["image", "imageUrl", "image_url", "thumbnail", "featuredImage", "featured_image"];
+// JSON values visited per island, so a multi-megabyte state dump can't stall the run
+const MAX_ISLAND_NODES: usize = 50_000;
+
+// --use-data-islands: client-rendered sites (Next.js and similar) ship their article lists as
+// JSON in a <script>, with no server-rendered anchors. Any array holding at least two objects
+// with a title and a URL is taken as an article list.
+fn extract_from_data_islands(document: &Html, base: &Url) -> Vec<Item> {
+    let mut items: Vec<Item> = Vec::new();
+    for id in &opts().data_island {
+        let Ok(sel) = Selector::parse(&format!(r#"script[id="{}"]"#, id.replace('"', ""))) else { continue };
+        for node in document.select(&sel) {
+            let text = node.text().collect::<String>();
+            if text.len() > opts().max_json_ld_bytes * 16 {
+                diag!("Skipping {} KB data island #{}", text.len() / 1024, id);
+                continue;
+            }
+            let Ok(json) = serde_json::from_str::<JsonValue>(&text) else {
+                if verbose() { diag!("Data island #{} is not valid JSON", id); }
+                continue;
+            };
+            let mut budget = MAX_ISLAND_NODES;
+            collect_island_items(&json, base, &mut budget, &mut items);
+        }
+    }
+    let mut seen = HashSet::new();
+    items.retain(|it| seen.insert(it.link.clone()));
+    items
+}
+
+fn collect_island_items(v: &JsonValue, base: &Url, budget: &mut usize, items: &mut Vec<Item>) {
+    if *budget == 0 { return; }
+    *budget -= 1;
+    match v {
+        JsonValue::Array(arr) => {
+            let found: Vec<Item> = arr.iter().filter_map(|x| island_item(x, base)).collect();
+            if found.len() >= 2 {
+                items.extend(found);
+                return;
+            }
+            for x in arr { collect_island_items(x, base, budget, items); }
+        }
+        JsonValue::Object(obj) => {
+            for x in obj.values() { collect_island_items(x, base, budget, items); }
+        }
+        _ => {}
+    }
+}
+
+// One article-shaped object: needs a title and a same-site or absolute URL.
+fn island_item(v: &JsonValue, base: &Url) -> Option<Item> {
+    let obj = v.as_object()?;
+    let text = |keys: &[&str]| keys.iter().find_map(|k| obj.get(*k).and_then(|x| x.as_str()).map(str::trim).filter(|s| !s.is_empty()).map(str::to_string));
+    let title = clean_title(&text(ISLAND_TITLE_KEYS)?);
+    let href = text(ISLAND_URL_KEYS)?;
+    if !(href.starts_with('/') || href.starts_with("http://") || href.starts_with("https://")) { return None; }
+    let link = base.join(&href).ok()?.to_string();
+    let pub_date = text(ISLAND_DATE_KEYS);
+    let description = text(ISLAND_DESC_KEYS).map(|d| fix_mojibake(&d));
+    let images: Vec<String> = ISLAND_IMAGE_KEYS.iter()
+        .find_map(|k| obj.get(*k))
+        .map(|img| {
+            let img = img.as_object().and_then(|o| o.get("src")).unwrap_or(img);
+            json_ld_images(img, base)
+        })
+        .unwrap_or_default();
+    Some(Item {
+        title,
+        link,
+        description,
+        updated: pub_date.clone(),
+        pub_date,
+        image: images.first().cloned(),
+        images,
+        ..Default::default()
+    })
+}
+
+// @graph/array members inspected per JSON-LD block; real pages have a few dozen at most.
+const MAX_JSON_LD_NODES: usize = 500;
+
 fn extract_from_json_ld(document: &Html, base: &Url) -> Option<Vec<Item>> {
-    let sel = Selector::parse("script[type=application/ld+json]").ok()?;
+    let sel = Selector::parse(r#"script[type="application/ld+json"]"#).ok()?;
     // We try several JSON-LD shapes: object, array, and @graph.
     for node in document.select(&sel) {
         if let Some(text) = node.first_child().and_then(|n| n.value().as_text()) {
-            if let Ok(json) = serde_json::from_str::<JsonValue>(text) {
+            if let Some(json) = parse_json_ld_block(text, base) {
                 let mut items = Vec::new();
 
                 // If it's an object with @graph, prefer graph members
@@ -144,34 +1503,24 @@ fn extract_from_json_ld(document: &Html, base: &Url) -> Option<Vec<Item>> {
                     let obj = json.as_object().unwrap();
                     if let Some(graph) = obj.get("@graph") {
                         if let Some(arr) = graph.as_array() {
-                            for v in arr {
-                                // only convert likely Article/NewsArticle nodes
-                                if is_jsonld_article_node(v) {
-                                    if let Some(it) = json_ld_to_item(v, base) {
-                                        items.push(it);
-                                    }
-                                }
+                            for v in arr.iter().take(MAX_JSON_LD_NODES) {
+                                // only convert likely Article/NewsArticle nodes (and live blogs)
+                                items.extend(json_ld_node_items(v, base));
                             }
                         }
                     }
 
                     // If no graph results, try to convert the root object if it's an Article
-                    if items.is_empty() && is_jsonld_article_node(&json) {
-                        if let Some(it) = json_ld_to_item(&json, base) {
-                            items.push(it);
-                        }
+                    if items.is_empty() {
+                        items.extend(json_ld_node_items(&json, base));
                     }
                 }
 
                 // If it's an array, iterate and pick Article-like nodes
                 if items.is_empty() && json.is_array() {
                     if let Some(arr) = json.as_array() {
-                        for v in arr {
-                            if is_jsonld_article_node(v) {
-                                if let Some(it) = json_ld_to_item(v, base) {
-                                    items.push(it);
-                                }
-                            }
+                        for v in arr.iter().take(MAX_JSON_LD_NODES) {
+                            items.extend(json_ld_node_items(v, base));
                         }
                     }
                 }
@@ -199,11 +1548,118 @@ fn extract_from_json_ld(document: &Html, base: &Url) -> Option<Vec<Item>> {
     None
 }
 
+// Items from one JSON-LD node: each update of a LiveBlogPosting, else the node itself when
+// it is article-like.
+fn json_ld_node_items(v: &JsonValue, base: &Url) -> Vec<Item> {
+    let live = live_blog_items(v, base);
+    if !live.is_empty() { return live; }
+    if !is_jsonld_article_node(v) { return Vec::new(); }
+    json_ld_to_item(v, base).into_iter().collect()
+}
+
+// Headline length for live-blog updates that only have a body.
+const LIVE_UPDATE_TITLE_CHARS: usize = 100;
+
+// A LiveBlogPosting's liveBlogUpdate entries as separate items. Updates usually share the
+// blog's URL with their own #anchor, so the anchored URL is also the item's GUID.
+fn live_blog_items(v: &JsonValue, base: &Url) -> Vec<Item> {
+    let is_live = |t: &JsonValue| t.as_str().is_some_and(|s| s.eq_ignore_ascii_case("LiveBlogPosting"));
+    let typ = v.get("@type");
+    if !typ.is_some_and(|t| is_live(t) || t.as_array().is_some_and(|a| a.iter().any(is_live))) {
+        return Vec::new();
+    }
+    let blog_url = v.get("url").and_then(|u| u.as_str())
+        .and_then(|u| base.join(u.trim()).ok())
+        .unwrap_or_else(|| base.clone());
+    let updates = match v.get("liveBlogUpdate") {
+        Some(JsonValue::Array(arr)) => arr.iter().take(MAX_JSON_LD_NODES).collect(),
+        Some(one @ JsonValue::Object(_)) => vec![one],
+        _ => Vec::new(),
+    };
+    updates.into_iter().enumerate().filter_map(|(i, update)| {
+        let mut obj = update.as_object()?.clone();
+        let body = obj.get("articleBody").and_then(|b| b.as_str()).map(fix_mojibake).filter(|b| !b.is_empty());
+        if obj.get("headline").or_else(|| obj.get("name")).and_then(|h| h.as_str()).is_none_or(|h| h.trim().is_empty()) {
+            obj.insert("headline".into(), summarize(body.as_deref()?, LIVE_UPDATE_TITLE_CHARS, SummaryUnit::Chars).into());
+        }
+        // @id is often a bare identifier; only anchors and absolute URLs are links
+        let id = obj.get("@id").and_then(|u| u.as_str()).filter(|u| u.starts_with('#') || u.starts_with("http"));
+        let link = obj.get("url").and_then(|u| u.as_str()).or(id)
+            .and_then(|u| blog_url.join(u.trim()).ok())
+            .unwrap_or_else(|| {
+                let mut u = blog_url.clone();
+                u.set_fragment(Some(&format!("update-{}", i + 1)));
+                u
+            });
+        obj.remove("url");
+        let mut it = json_ld_to_item(&JsonValue::Object(obj), &blog_url)?;
+        if it.description.is_none() { it.description = body; }
+        it.link = link.to_string();
+        it.guid = Some(link.to_string());
+        it.original_link = None;
+        Some(it)
+    }).collect()
+}
+
+// Phrases that only appear on error/placeholder pages, matched anywhere.
+const ERROR_PHRASES: &[&str] = &[
+    "page not found", "404 not found", "page cannot be found", "page could not be found", "page doesn't exist",
+    "page does not exist", "uh-oh", "uh oh", "an error occurred", "sorry, an error",
+];
+
+// Wording that hints at an error page but also shows up in real headlines ("Sorry, Not Sorry",
+// "Something Went Wrong at the Fed").
+static RE_WEAK_ERROR: Lazy<Regex> = Lazy::new(|| Regex::new(r"\b(error|sorry|oops|404|not found|something went wrong)\b").unwrap());
+
+static RE_TRAILING_COMMA: Lazy<Regex> = Lazy::new(|| Regex::new(r",(\s*[}\]])").unwrap());
+
+// Parse one JSON-LD block. Malformed blocks (common in the wild) get a lenient repair pass:
+// strip CDATA/comment wrappers, drop trailing commas, decode HTML-escaped quotes.
+fn parse_json_ld_block(text: &str, base: &Url) -> Option<JsonValue> {
+    if text.len() > opts().max_json_ld_bytes {
+        diag!("Skipping {} KB JSON-LD block on {} (over --max-json-ld-bytes)", text.len() / 1024, base.host_str().unwrap_or(""));
+        return None;
+    }
+    let err = match serde_json::from_str::<JsonValue>(text) {
+        Ok(json) => return Some(json),
+        Err(e) => e,
+    };
+    let repaired = repair_json_ld(text);
+    match serde_json::from_str::<JsonValue>(&repaired) {
+        Ok(json) => {
+            if verbose() { diag!("Repaired malformed JSON-LD on {} ({})", base.host_str().unwrap_or(""), err); }
+            Some(json)
+        }
+        Err(_) => {
+            if verbose() {
+                let snippet: String = text.trim().chars().take(120).collect();
+                diag!("Skipping unparseable JSON-LD on {}: {} near {:?}", base.host_str().unwrap_or(""), err, snippet);
+            }
+            None
+        }
+    }
+}
+
+fn repair_json_ld(text: &str) -> String {
+    let mut s = text.trim();
+    for (open, close) in [("<![CDATA[", "]]>"), ("<!--", "-->"), ("//<![CDATA[", "//]]>")] {
+        if let Some(inner) = s.strip_prefix(open) {
+            s = inner.strip_suffix(close).unwrap_or(inner).trim();
+        }
+    }
+    let mut out = RE_TRAILING_COMMA.replace_all(s, "$1").to_string();
+    if out.contains("&quot;") || out.contains("&#34;") {
+        out = decode_html_entities(&out).to_string();
+    }
+    // raw control characters (newlines/tabs inside strings) are invalid JSON
+    out.chars().map(|c| if c.is_control() { ' ' } else { c }).collect()
+}
+
 // Heuristic to detect pages that are error/placeholder pages and should be skipped.
+// Whole error phrases are decisive; lone words like "error"/"sorry" only count when the page has little content.
 fn is_error_page(document: &Html, title: &str, description: &Option<String>) -> bool {
     let low_title = title.to_lowercase();
-    // Common error or non-content titles (login/sign-in, 404, error pages)
-    if low_title.contains("uh-oh") || low_title.contains("uh oh") || low_title.contains("error") || low_title.contains("404") || low_title.contains("page not found") || low_title.contains("not found") || low_title.contains("we're sorry") || low_title.contains("sorry") {
+    if ERROR_PHRASES.iter().any(|p| low_title.contains(p)) {
         return true;
     }
 
@@ -212,22 +1668,87 @@ fn is_error_page(document: &Html, title: &str, description: &Option<String>) ->
         return true;
     }
 
-    if let Some(d) = description {
-        let ld = d.to_lowercase();
-        if ld.contains("error") || ld.contains("not found") || ld.contains("page not found") || ld.contains("uh-oh") { return true; }
+    let low_desc = description.as_ref().map(|d| d.to_lowercase()).unwrap_or_default();
+    if ERROR_PHRASES.iter().any(|p| low_desc.contains(p)) {
+        return true;
     }
 
-    // Inspect body text for common error phrases (small scan)
+    // Inspect body text for error phrases (small scan)
+    let mut body_hint = false;
     if let Ok(sel_body) = Selector::parse("body") {
         if let Some(body) = document.select(&sel_body).next() {
             let text = body.text().take(200).collect::<Vec<_>>().join(" ").to_lowercase();
-            if text.contains("uh-oh") || text.contains("page not found") || text.contains("an error occurred") || text.contains("we\u{2019}re sorry") || text.contains("we are sorry") || text.contains("sorry, an error") {
+            if ERROR_PHRASES.iter().any(|p| text.contains(p)) {
                 return true;
             }
+            body_hint = text.contains("we\u{2019}re sorry") || text.contains("we're sorry") || text.contains("we are sorry");
         }
     }
 
-    false
+    let weak = RE_WEAK_ERROR.is_match(&low_title) || RE_WEAK_ERROR.is_match(&low_desc) || body_hint;
+    weak && has_thin_content(document)
+}
+
+// "Not found" wording that avoids the classic phrases, seen on soft-404 pages.
+const SOFT_404_PHRASES: &[&str] = &[
+    "no longer available", "page unavailable", "story unavailable", "article unavailable", "content unavailable",
+    "can't find", "cannot find", "couldn't find", "could not find", "doesn't exist", "does not exist",
+    "has been removed", "nothing here", "isn't here", "something went wrong",
+];
+
+// Error pages served with HTTP 200: a canonical (or og:url) pointing at the homepage, noindex
+// together with error wording, or a near-empty page whose title/heading reads like an error.
+fn is_soft_404(document: &Html, page_url: &Url) -> bool {
+    let thin = has_thin_content(document);
+    let first = |css: &str, attr: &str| Selector::parse(css).ok()
+        .and_then(|sel| document.select(&sel).next().and_then(|el| el.value().attr(attr).map(str::to_string)));
+
+    let canonical_home = page_url.path() != "/" && [first(r#"link[rel="canonical"]"#, "href"), first(r#"meta[property="og:url"]"#, "content")]
+        .into_iter().flatten()
+        .filter_map(|href| page_url.join(href.trim()).ok())
+        .any(|u| u.host_str() == page_url.host_str() && u.path() == "/" && u.query().is_none());
+    if canonical_home && thin { return true; }
+
+    let heading = Selector::parse("title, h1").ok()
+        .map(|sel| document.select(&sel).map(|el| el.text().collect::<String>()).collect::<Vec<_>>().join(" ").to_lowercase())
+        .unwrap_or_default();
+    let wording = SOFT_404_PHRASES.iter().any(|p| heading.contains(p)) || ERROR_PHRASES.iter().any(|p| heading.contains(p));
+    let noindex = first(r#"meta[name="robots" i]"#, "content").is_some_and(|c| c.to_lowercase().contains("noindex"));
+    (noindex && (wording || RE_WEAK_ERROR.is_match(&heading))) || (thin && wording)
+}
+
+// True when the page has almost no paragraph text, as error/placeholder pages do.
+fn has_thin_content(document: &Html) -> bool {
+    const MIN_CONTENT_CHARS: usize = 400;
+    let mut total = 0;
+    if let Ok(sel_p) = Selector::parse("p") {
+        for p in document.select(&sel_p) {
+            total += p.text().map(|t| t.trim().len()).sum::<usize>();
+            if total >= MIN_CONTENT_CHARS { return false; }
+        }
+    }
+    true
+}
+
+// Markers of common consent-management platforms (OneTrust, Usercentrics, Cookiebot, Quantcast, Sourcepoint, Didomi).
+const CONSENT_SELECTORS: &str = "#onetrust-banner-sdk, #onetrust-consent-sdk, #usercentrics-root, #CybotCookiebotDialog, \
+    .qc-cmp2-container, [id^=sp_message_container], .fc-consent-root, #didomi-host, .cookie-consent, .cookie-consent-banner";
+
+// Detect cookie-consent interstitials: a CMP marker on a page without real content, or a body that is mostly a form.
+// These often carry the article's <title>, so is_error_page alone lets them through.
+fn is_consent_wall(document: &Html) -> bool {
+    if let Ok(sel) = Selector::parse(CONSENT_SELECTORS) {
+        if document.select(&sel).next().is_some() && has_thin_content(document) {
+            return true;
+        }
+    }
+    let (Ok(sel_body), Ok(sel_form)) = (Selector::parse("body"), Selector::parse("form")) else { return false };
+    let Some(body) = document.select(&sel_body).next() else { return false };
+    let text_len = |el: ElementRef| el.text().map(|t| t.trim().len()).sum::<usize>();
+    let body_len = text_len(body);
+    let form_len: usize = body.select(&sel_form).map(text_len).sum();
+    body_len > 0 && form_len * 10 >= body_len * 8
+        && body.text().collect::<String>().to_lowercase().contains("cookie")
 }
 
 fn is_jsonld_article_node(v: &JsonValue) -> bool {
@@ -237,12 +1758,12 @@ fn is_jsonld_article_node(v: &JsonValue) -> bool {
         if let Some(t) = obj.get("@type").or_else(|| obj.get("type")) {
             if t.is_string() {
                 let s = t.as_str().unwrap_or("").to_lowercase();
-                return s.contains("article") || s.contains("newsarticle") || s.contains("report");
+                return s.contains("article") || s.contains("newsarticle") || s.contains("report") || s == "podcastepisode";
             } else if t.is_array() {
                 for el in t.as_array().unwrap() {
                     if let Some(s) = el.as_str() {
                         let sl = s.to_lowercase();
-                        if sl.contains("article") || sl.contains("newsarticle") || sl.contains("report") { return true; }
+                        if sl.contains("article") || sl.contains("newsarticle") || sl.contains("report") || sl == "podcastepisode" { return true; }
                     }
                 }
             }
@@ -263,40 +1784,127 @@ fn json_ld_to_item(v: &JsonValue, base: &Url) -> Option<Item> {
     }
 
     let title_raw = obj.get("headline").and_then(|s| s.as_str()).or_else(|| obj.get("name").and_then(|s| s.as_str())).map(|s| s.to_string())?;
-    let title = fix_mojibake(&title_raw);
+    let title = clean_title(&title_raw);
     // normalize link (make absolute when possible)
-    let link = obj.get("url").and_then(|s| s.as_str()).and_then(|s| normalize_maybe_url(base, s)).or_else(|| Some(base.as_str().to_string()))?;
-    let description = obj.get("description").and_then(|s| s.as_str()).map(|s| fix_mojibake(&s.to_string()));
+    let raw_link = obj.get("url").and_then(|s| s.as_str());
+    let link = raw_link.and_then(|s| normalize_maybe_url(base, s)).or_else(|| Some(base.as_str().to_string()))?;
+    let original_link = raw_link
+        .and_then(|s| base.join(s.trim()).ok())
+        .map(String::from)
+        .filter(|orig| *orig != link);
+    let description = obj.get("description").and_then(|s| s.as_str()).map(fix_mojibake);
     let pub_date = obj.get("datePublished").and_then(|s| s.as_str()).map(|s| s.to_string());
-    // image can be string or object or array
-    let image = if let Some(img) = obj.get("image") {
-        if img.is_string() {
-            img.as_str().and_then(|s| normalize_maybe_url(base, s))
-        } else if img.is_object() {
-            img.get("url").and_then(|u| u.as_str()).and_then(|s| normalize_maybe_url(base, s))
-        } else if img.is_array() {
-            img.as_array().and_then(|arr| arr.get(0)).and_then(|v| v.as_str()).and_then(|s| normalize_maybe_url(base, s))
-        } else {
-            None
+    let updated = obj.get("dateModified").and_then(|s| s.as_str()).map(|s| s.to_string()).or_else(|| pub_date.clone());
+    let images = obj.get("image").map(|img| json_ld_images(img, base)).unwrap_or_default();
+    let image = images.first().cloned();
+    let image_length = obj.get("image")
+        .map(|img| img.as_array().and_then(|arr| arr.first()).unwrap_or(img))
+        .and_then(|img| img.get("contentSize"))
+        .and_then(parse_content_size);
+
+    // author may be a string, a Person object, or an array of either; use the first
+    let author_node = obj.get("author").map(|a| a.as_array().and_then(|arr| arr.first()).unwrap_or(a));
+    let author = author_node
+        .and_then(|a| a.as_str().or_else(|| a.get("name").and_then(|n| n.as_str())))
+        .map(fix_mojibake)
+        .filter(|a| !a.is_empty());
+    let author_image = author_node.and_then(|a| a.get("image")).and_then(|img| json_ld_images(img, base).into_iter().next());
+    let publisher_node = obj.get("publisher");
+    let publisher = publisher_node.and_then(|p| p.get("name")).and_then(|n| n.as_str()).map(fix_mojibake);
+    let publisher_logo = publisher_node.and_then(|p| p.get("logo")).and_then(|img| json_ld_images(img, base).into_iter().next());
+
+    // podcast episodes: the AudioObject in associatedMedia/audio, running time and number
+    let audio_node = obj.get("associatedMedia").or_else(|| obj.get("audio"))
+        .map(|a| a.as_array().and_then(|arr| arr.first()).unwrap_or(a));
+    let audio = audio_node
+        .and_then(|a| a.get("contentUrl").or_else(|| a.get("url")).and_then(|u| u.as_str()).or_else(|| a.as_str()))
+        .and_then(|u| normalize_maybe_url(base, u));
+    let audio_type = audio_node.and_then(|a| a.get("encodingFormat")).and_then(|f| f.as_str()).map(str::to_string);
+    let audio_length = audio_node.and_then(|a| a.get("contentSize")).and_then(parse_content_size);
+    let duration = ["duration", "timeRequired"].iter()
+        .find_map(|k| obj.get(*k).or_else(|| audio_node.and_then(|a| a.get(*k))))
+        .and_then(|d| d.as_str().map(str::to_string).or_else(|| d.as_u64().map(|n| n.to_string())));
+    let episode = obj.get("episodeNumber")
+        .and_then(|n| n.as_u64().or_else(|| n.as_str().and_then(|s| s.trim().parse().ok())));
+    let mut categories = Vec::new();
+    for key in ["articleSection", "keywords"] {
+        let values = match obj.get(key) {
+            Some(JsonValue::Array(arr)) => arr.iter().filter_map(|v| v.as_str()).map(str::to_string).collect(),
+            // keywords is often one comma-separated string
+            Some(JsonValue::String(s)) if key == "keywords" => s.split(',').map(str::to_string).collect(),
+            Some(JsonValue::String(s)) => vec![s.clone()],
+            _ => Vec::new(),
+        };
+        push_categories(&mut categories, values);
+    }
+    let word_count = obj.get("wordCount")
+        .and_then(|n| n.as_u64().or_else(|| n.as_str().and_then(|s| s.trim().replace(',', "").parse().ok())))
+        .filter(|n| *n > 0);
+
+    Some(Item {
+        title, link, description, pub_date, updated, image, image_length, images, original_link,
+        author, author_image, publisher, publisher_logo, audio, audio_type, audio_length, duration, episode, word_count, categories,
+        ..Default::default()
+    })
+}
+
+// Parse a schema.org contentSize: a number of bytes or a string like "24 KB" / "1.5MB".
+fn parse_content_size(v: &JsonValue) -> Option<u64> {
+    if let Some(n) = v.as_u64() { return Some(n); }
+    let s = v.as_str()?.trim().to_lowercase();
+    let split = s.find(|c: char| !(c.is_ascii_digit() || c == '.')).unwrap_or(s.len());
+    let num: f64 = s[..split].parse().ok()?;
+    let mult = match s[split..].trim() {
+        "" | "b" | "bytes" => 1.0,
+        "kb" | "k" => 1024.0,
+        "mb" | "m" => 1024.0 * 1024.0,
+        "gb" | "g" => 1024.0 * 1024.0 * 1024.0,
+        _ => return None,
+    };
+    Some((num * mult) as u64)
+}
+
+// Collect image URLs from a JSON-LD image value: string, ImageObject, or an array of either.
+fn json_ld_images(img: &JsonValue, base: &Url) -> Vec<String> {
+    let mut out: Vec<String> = Vec::new();
+    let mut push = |v: &JsonValue| {
+        let raw = v.as_str().or_else(|| v.get("url").and_then(|u| u.as_str()));
+        if let Some(u) = raw.and_then(|s| normalize_maybe_url(base, s)) {
+            if !out.contains(&u) { out.push(u); }
         }
-    } else {
-        None
     };
+    if let Some(arr) = img.as_array() {
+        arr.iter().for_each(&mut push);
+    } else {
+        push(img);
+    }
+    out.truncate(opts().max_images.max(1));
+    out
+}
 
-    Some(Item { title, link, description, pub_date, image })
+// --unicode-form for one string.
+fn normalize_unicode(s: String, form: UnicodeForm) -> String {
+    match form {
+        UnicodeForm::Nfkc => s.nfkc().collect(),
+        UnicodeForm::Nfc => s.nfc().collect(),
+        UnicodeForm::None => s,
+    }
 }
 
 // Attempt to repair common mojibake where UTF-8 bytes were decoded as Latin-1/Windows-1252
 fn fix_mojibake(s: &str) -> String {
     // Normalize and repair mojibake / whitespace across extracted strings.
     // Strategy:
-    // 1. If the string appears clean, run Unicode NFKC normalization and collapse whitespace.
+    // 1. If the string appears clean, run Unicode normalization (--unicode-form) and collapse whitespace.
     // 2. Otherwise attempt up to 3 passes of: reinterpret low-8-bit bytes as UTF-8, else decode as Windows-1252.
     // 3. After decoding passes, perform Unicode normalization and whitespace collapse.
 
     fn collapse_and_normalize(inp: String) -> String {
-        let mut out = inp.nfkc().collect::<String>();
+        let mut out = normalize_unicode(inp, opts().unicode_form);
         out = out.replace('\u{00A0}', " ");
+        if let Some(mode) = opts().strip_symbols {
+            out = strip_symbols(&out, mode);
+        }
         out = RE_WHITESPACE.replace_all(&out, " ").to_string();
         out.trim().to_string()
     }
@@ -336,7 +1944,173 @@ fn fix_mojibake(s: &str) -> String {
     collapse_and_normalize(cur)
 }
 
+// Repair/normalize a raw title and apply the configured --title-case policy.
+fn clean_title(raw: &str) -> String {
+    apply_title_case(&fix_mojibake(raw), opts().title_case)
+}
+
+// Short words kept lowercase inside title-cased headlines (unless first).
+const TITLE_SMALL_WORDS: &[&str] = &[
+    "a", "an", "and", "as", "at", "but", "by", "for", "in", "nor", "of", "on", "or", "per", "the", "to", "vs", "via",
+];
+
+// Acronyms that contain vowels and therefore can't be recognized by shape alone.
+const KNOWN_ACRONYMS: &[&str] = &[
+    "NASA", "NATO", "CEO", "CFO", "COO", "AI", "EU", "UN", "US", "USA", "UK", "UAE", "IOC", "OPEC", "UNESCO",
+    "UNICEF", "FIFA", "UEFA", "COVID", "AIDS", "IRA", "IRS", "ICE", "OECD", "IPO", "IMF", "FBI", "CIA", "NBA", "NHL",
+    "ABC", "BBC", "HBO", "AP", "NY", "NYC", "NYPD", "LA", "GOP", "EV", "TV", "II", "III", "IV",
+];
+
+// Vowel-less honorifics and abbreviations that are words, not acronyms (MR. SMITH -> Mr. Smith).
+const TITLE_ABBREVIATIONS: &[&str] = &["MR", "MRS", "MS", "DR", "ST", "JR", "SR", "VS", "MT", "FT", "PT"];
+
+// A word without its possessive ending ("NASA'S" -> "NASA"), and the ending if it had one.
+fn split_possessive(word: &str) -> (&str, &str) {
+    let end = word.trim_end_matches(|c: char| !c.is_alphanumeric()).len();
+    ["'S", "'s", "\u{2019}S", "\u{2019}s"].iter()
+        .find_map(|p| word[..end].strip_suffix(p).map(|stem| (stem, &word[stem.len()..])))
+        .unwrap_or((word, ""))
+}
+
+// Whether an ALL-CAPS word looks like an acronym (known, or short with no vowels like CNN/NFL/WSJ).
+fn is_acronym(word: &str) -> bool {
+    let letters: String = split_possessive(word).0.chars().filter(|c| c.is_alphanumeric()).collect();
+    if letters.is_empty() || TITLE_ABBREVIATIONS.contains(&letters.as_str()) { return false; }
+    if KNOWN_ACRONYMS.contains(&letters.as_str()) { return true; }
+    letters.chars().count() <= 4 && !letters.chars().any(|c| "AEIOUY".contains(c))
+}
+
+// Uppercase the first alphabetic character, lowercase the rest (keeps leading quotes/punctuation).
+fn capitalize_word(word: &str) -> String {
+    let mut out = String::with_capacity(word.len());
+    let mut done = false;
+    for c in word.chars() {
+        if !done && c.is_alphabetic() {
+            out.extend(c.to_uppercase());
+            done = true;
+        } else {
+            out.extend(c.to_lowercase());
+        }
+    }
+    out
+}
+
+// Words with capitals after the first letter (iPhone, McDonald, eBay) are deliberate and left alone.
+fn has_inner_capitals(word: &str) -> bool {
+    word.chars().filter(|c| c.is_alphabetic()).skip(1).any(|c| c.is_uppercase())
+        && word.chars().any(|c| c.is_lowercase())
+}
+
+// Re-case one word (hyphenated parts handled separately). `first` marks the start of a title or clause.
+fn recase_word(word: &str, mode: TitleCase, shouting: bool, first: bool) -> String {
+    let parts: Vec<String> = word.split('-').enumerate().map(|(i, part)| {
+        let first_part = first && i == 0;
+        let upper = part.chars().any(|c| c.is_alphabetic()) && !part.chars().any(|c| c.is_lowercase());
+        if has_inner_capitals(part) { return part.to_string(); }
+        // in a shouting title only known acronyms survive; in mixed case any ALL-CAPS word is deliberate
+        if upper && (if shouting { is_acronym(part) } else { part.chars().filter(|c| c.is_alphabetic()).count() > 1 }) {
+            // NASA'S -> NASA's
+            let (stem, possessive) = split_possessive(part);
+            return format!("{}{}", stem, possessive.to_lowercase());
+        }
+        let lower = part.to_lowercase();
+        let bare: String = lower.chars().filter(|c| c.is_alphanumeric()).collect();
+        match mode {
+            TitleCase::Smart if !first_part && i == 0 && TITLE_SMALL_WORDS.contains(&bare.as_str()) => lower,
+            TitleCase::Smart => capitalize_word(part),
+            _ if first_part => capitalize_word(part),
+            _ => lower,
+        }
+    }).collect();
+    parts.join("-")
+}
+
+// Apply the --title-case policy. `smart` only rewrites shouting (no lowercase letters) titles;
+// `lower` converts to sentence case while keeping acronyms and mixed-case brand names.
+fn apply_title_case(title: &str, mode: TitleCase) -> String {
+    if mode == TitleCase::None { return title.to_string(); }
+    let shouting = title.chars().any(|c| c.is_alphabetic()) && !title.chars().any(|c| c.is_lowercase());
+    if mode == TitleCase::Smart && !shouting { return title.to_string(); }
+    let mut sentence_start = true;
+    title.split(' ')
+        .map(|w| {
+            let out = recase_word(w, mode, shouting, sentence_start);
+            if !w.is_empty() { sentence_start = w.ends_with(':') || w.ends_with('.') && !is_acronym(w); }
+            out
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// Trim text to `n` words, sentences or characters without cutting mid-word, appending an ellipsis when shortened.
+fn summarize(text: &str, n: usize, unit: SummaryUnit) -> String {
+    let text = text.trim();
+    let cut = match unit {
+        SummaryUnit::Words => text.split_whitespace().nth(n).map(|w| {
+            // byte offset of the (n+1)th word; everything before it is kept
+            w.as_ptr() as usize - text.as_ptr() as usize
+        }),
+        SummaryUnit::Sentences => {
+            let mut count = 0;
+            let mut chars = text.char_indices().peekable();
+            let mut at = None;
+            while let Some((i, c)) = chars.next() {
+                let ends = matches!(c, '.' | '!' | '?' | '\u{3002}' | '\u{FF01}' | '\u{FF1F}');
+                let boundary = chars.peek().map(|(_, nc)| nc.is_whitespace()).unwrap_or(false) || c > '\u{3000}';
+                if ends && boundary {
+                    count += 1;
+                    if count == n { at = Some(i + c.len_utf8()); break; }
+                }
+            }
+            at.filter(|&i| i < text.len())
+        }
+        SummaryUnit::Chars => text.char_indices().nth(n).map(|(i, _)| {
+            // back off to the last whitespace so we don't split a word
+            text[..i].rfind(char::is_whitespace).filter(|&ws| ws > 0).unwrap_or(i)
+        }),
+    };
+    match cut {
+        Some(i) => {
+            let kept = text[..i].trim_end();
+            if unit == SummaryUnit::Sentences { format!("{} …", kept) } else { format!("{}…", kept.trim_end_matches([',', ';', ':'])) }
+        }
+        None => text.to_string(),
+    }
+}
+
 // Try to parse a URL as absolute, or join it with base when relative.
+// AMP cache paths: /c/ document, /v/ viewer, /i/ image, /ii/<transform>/ resized image,
+// /r/ resource; an "s/" segment after that means the origin is https.
+static RE_AMP_CACHE_PATH: Lazy<Regex> = Lazy::new(|| Regex::new(r"^/(?:[cvir]|ii/[^/]+)/(s/)?([^/]+\.[^/]+)(/.*)?$").unwrap());
+
+// Origin form of an AMP-cache URL (https://www-example-com.cdn.ampproject.org/i/s/www.example.com/a.jpg
+// -> https://www.example.com/a.jpg), or of a Google AMP viewer link (google.com/amp/s/...).
+// Viewer-only query parameters are dropped.
+fn amp_cache_origin(u: &Url) -> Option<String> {
+    let host = u.host_str()?.to_ascii_lowercase();
+    let path = if host == "cdn.ampproject.org" || host.ends_with(".cdn.ampproject.org") {
+        u.path().to_string()
+    } else if matches!(host.as_str(), "google.com" | "www.google.com") {
+        u.path().strip_prefix("/amp").filter(|p| p.starts_with('/')).map(|p| format!("/c{}", p))?
+    } else {
+        return None;
+    };
+    let caps = RE_AMP_CACHE_PATH.captures(&path)?;
+    let scheme = if caps.get(1).is_some() { "https" } else { "http" };
+    let rest = caps.get(3).map_or("/", |m| m.as_str());
+    let mut origin = Url::parse(&format!("{}://{}{}", scheme, &caps[2], rest)).ok()?;
+    let kept: Vec<(String, String)> = u.query_pairs()
+        .filter(|(k, _)| !matches!(k.as_ref(), "amp_js_v" | "amp_gsa" | "usqp" | "amp_r" | "amp_ct" | "amp_tf"))
+        .map(|(k, v)| (k.into_owned(), v.into_owned()))
+        .collect();
+    if kept.is_empty() {
+        origin.set_query(None);
+    } else {
+        origin.query_pairs_mut().extend_pairs(kept);
+    }
+    Some(origin.into())
+}
+
 fn normalize_maybe_url(base: &Url, s: &str) -> Option<String> {
     // quick reject empty
     let s = s.trim();
@@ -344,6 +2118,9 @@ fn normalize_maybe_url(base: &Url, s: &str) -> Option<String> {
 
     // If it already parses as absolute URL, sanitize query-embedded urls
     if let Ok(u) = Url::parse(s) {
+        if let Some(origin) = amp_cache_origin(&u) {
+            return Some(origin);
+        }
         if let Some(inner) = extract_inner_query_url(&u) {
             return Some(inner);
         }
@@ -352,6 +2129,9 @@ fn normalize_maybe_url(base: &Url, s: &str) -> Option<String> {
 
     // Try to join relative URLs against base
     if let Ok(u) = base.join(s) {
+        if let Some(origin) = amp_cache_origin(&u) {
+            return Some(origin);
+        }
         if let Some(inner) = extract_inner_query_url(&u) {
             return Some(inner);
         }
@@ -361,7 +2141,7 @@ fn normalize_maybe_url(base: &Url, s: &str) -> Option<String> {
     // Last resort: look for encoded url=... inside the string
     if let Some(idx) = s.find("url=") {
         let after = &s[idx + 4..];
-        for (_k, v) in form_urlencoded::parse(after.as_bytes()) {
+        if let Some((_k, v)) = form_urlencoded::parse(after.as_bytes()).next() {
             return Some(v.into_owned());
         }
     }
@@ -398,24 +2178,345 @@ fn allowed_domain(u: &Url) -> bool {
     false
 }
 
+// Root directory for persistent artifacts: --state-dir, else $XDG_STATE_HOME/html2rss, else ~/.local/state/html2rss.
+// Individual state files live here under conventional names unless their own path flag overrides them.
+fn state_dir() -> PathBuf {
+    if let Some(dir) = &opts().state_dir {
+        return dir.clone();
+    }
+    if let Some(xdg) = env::var_os("XDG_STATE_HOME").filter(|v| !v.is_empty()) {
+        return PathBuf::from(xdg).join("html2rss");
+    }
+    if let Some(home) = env::var_os("HOME").filter(|v| !v.is_empty()) {
+        return PathBuf::from(home).join(".local/state/html2rss");
+    }
+    env::temp_dir().join("html2rss")
+}
+
+// Resolve the state directory, creating it if missing.
+fn ensure_state_dir() -> io::Result<PathBuf> {
+    let dir = state_dir();
+    fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+// --checkpoint: the pages a crawl has finished and the items collected so far, rewritten after
+// every page. A checkpoint left by a different start URL is ignored.
+struct Checkpoint {
+    path: PathBuf,
+    start: String,
+    processed: Vec<String>,
+    items: Vec<Item>,
+}
+
+impl Checkpoint {
+    fn load(path: PathBuf, start: &str) -> Self {
+        let mut cp = Checkpoint { path, start: start.to_string(), processed: Vec::new(), items: Vec::new() };
+        let Ok(text) = fs::read_to_string(&cp.path) else { return cp };
+        match serde_json::from_str::<JsonValue>(&text) {
+            Ok(json) if json.get("start").and_then(|s| s.as_str()) == Some(start) => {
+                let entries = |k: &str| json.get(k).and_then(|a| a.as_array()).cloned().unwrap_or_default();
+                cp.processed = entries("processed").iter().filter_map(|u| u.as_str().map(str::to_string)).collect();
+                cp.items = entries("items").iter().filter_map(checkpoint_to_item).collect();
+            }
+            Ok(_) => diag!("Ignoring checkpoint {} (written for another URL)", cp.path.display()),
+            Err(e) => diag!("Ignoring unreadable checkpoint {}: {}", cp.path.display(), e),
+        }
+        cp
+    }
+
+    // Written to a temporary file and renamed, so an interruption never leaves half a checkpoint.
+    fn save(&self) -> io::Result<()> {
+        let json = serde_json::json!({
+            "start": self.start,
+            "processed": self.processed,
+            "items": self.items.iter().map(item_to_checkpoint).collect::<Vec<_>>(),
+        });
+        let tmp = self.path.with_extension("tmp");
+        fs::write(&tmp, json.to_string())?;
+        fs::rename(&tmp, &self.path)
+    }
+}
+
+// The crawl runs on one thread; keeping the checkpoint thread-local keeps tests independent.
+thread_local! {
+    static CHECKPOINT: RefCell<Option<Checkpoint>> = const { RefCell::new(None) };
+}
+
+// Load (or begin) the checkpoint at `path` and skip the pages it has already done.
+fn start_checkpoint(path: PathBuf, start: &str, visited: &VisitedFetcher) {
+    let cp = Checkpoint::load(path, start);
+    if !cp.processed.is_empty() {
+        diag!("Resuming from checkpoint {}: {} page(s) done, {} item(s)", cp.path.display(), cp.processed.len(), cp.items.len());
+    }
+    visited.mark_visited(&cp.processed);
+    CHECKPOINT.with_borrow_mut(|slot| *slot = Some(cp));
+}
+
+fn checkpoint_items() -> Vec<Item> {
+    CHECKPOINT.with_borrow(|cp| cp.as_ref().map(|cp| cp.items.clone()).unwrap_or_default())
+}
+
+// Record a finished page and the items so far.
+fn checkpoint_progress(done: &Url, items: &[Item]) {
+    CHECKPOINT.with_borrow_mut(|cp| {
+        let Some(cp) = cp else { return };
+        cp.processed.push(done.to_string());
+        cp.items = items.to_vec();
+        if let Err(e) = cp.save() { diag!("Failed to save checkpoint {}: {}", cp.path.display(), e); }
+    });
+}
+
+// The crawl completed: nothing to resume.
+fn finish_checkpoint() {
+    if let Some(cp) = CHECKPOINT.with_borrow_mut(Option::take) {
+        if let Err(e) = fs::remove_file(&cp.path).or_else(|e| if e.kind() == io::ErrorKind::NotFound { Ok(()) } else { Err(e) }) {
+            diag!("Failed to remove checkpoint {}: {}", cp.path.display(), e);
+        }
+    }
+}
+
+// Every Item field, for the checkpoint file (item_json is the smaller public ndjson shape).
+fn item_to_checkpoint(it: &Item) -> JsonValue {
+    serde_json::json!({
+        "title": it.title, "link": it.link, "description": it.description,
+        "pub_date": it.pub_date, "updated": it.updated,
+        "image": it.image, "image_length": it.image_length, "images": it.images,
+        "image_caption": it.image_caption, "image_credit": it.image_credit,
+        "original_link": it.original_link, "guid": it.guid, "language": it.language,
+        "audio": it.audio, "audio_type": it.audio_type, "audio_length": it.audio_length,
+        "duration": it.duration, "episode": it.episode, "word_count": it.word_count,
+        "player": it.player, "video": it.video, "video_type": it.video_type, "video_length": it.video_length,
+        "categories": it.categories,
+        "author": it.author, "author_image": it.author_image,
+        "publisher": it.publisher, "publisher_logo": it.publisher_logo,
+    })
+}
+
+fn checkpoint_to_item(v: &JsonValue) -> Option<Item> {
+    let s = |k: &str| v.get(k).and_then(|x| x.as_str()).map(str::to_string);
+    let n = |k: &str| v.get(k).and_then(|x| x.as_u64());
+    let list = |k: &str| v.get(k).and_then(|x| x.as_array())
+        .map(|a| a.iter().filter_map(|x| x.as_str().map(str::to_string)).collect())
+        .unwrap_or_default();
+    Some(Item {
+        title: s("title")?, link: s("link")?, description: s("description"),
+        pub_date: s("pub_date"), updated: s("updated"),
+        image: s("image"), image_length: n("image_length"), images: list("images"),
+        image_caption: s("image_caption"), image_credit: s("image_credit"),
+        original_link: s("original_link"), guid: s("guid"), language: s("language"),
+        audio: s("audio"), audio_type: s("audio_type"), audio_length: n("audio_length"),
+        duration: s("duration"), episode: n("episode"), word_count: n("word_count"),
+        player: s("player"), video: s("video"), video_type: s("video_type"), video_length: n("video_length"),
+        categories: list("categories"),
+        author: s("author"), author_image: s("author_image"),
+        publisher: s("publisher"), publisher_logo: s("publisher_logo"),
+    })
+}
+
+// Per-host credentials from --host-auth-file: (host, headers) pairs, parsed once at startup.
+type HostAuth = Vec<(String, Vec<(HeaderName, HeaderValue)>)>;
+static HOST_AUTH: OnceCell<HostAuth> = OnceCell::new();
+
+// Load and validate the host auth file. Keys must be bare host names, optionally with a leading "."
+// to cover subdomains too; values map header names to strings.
+fn load_host_auth(path: &Path) -> Result<HostAuth, Box<dyn Error>> {
+    let text = fs::read_to_string(path)?;
+    let json: JsonValue = serde_json::from_str(&text)?;
+    let obj = json.as_object().ok_or("host auth file must be a JSON object keyed by host")?;
+    let mut out = Vec::new();
+    for (host, headers) in obj {
+        let h = host.trim().to_lowercase();
+        if h.trim_start_matches('.').is_empty() || h.contains('/') || h.contains(':') || h.contains(char::is_whitespace) {
+            return Err(format!("invalid host key in host auth file: {:?}", host).into());
+        }
+        let map = headers.as_object().ok_or_else(|| format!("headers for {} must be a JSON object", host))?;
+        let mut pairs = Vec::new();
+        for (name, value) in map {
+            let value = value.as_str().ok_or_else(|| format!("header {} for {} must be a string", name, host))?;
+            pairs.push((HeaderName::from_bytes(name.as_bytes())?, HeaderValue::from_str(value)?));
+        }
+        out.push((h, pairs));
+    }
+    Ok(out)
+}
+
+// Headers configured for this URL's host.
+fn host_auth_headers(url: &Url) -> Option<&'static [(HeaderName, HeaderValue)]> {
+    host_auth_for(HOST_AUTH.get()?, url)
+}
+
+// "example.com" matches that host only; ".example.com" matches it and its subdomains, on a
+// label boundary so "badexample.com" never does. An exact key wins over a dotted one.
+fn host_auth_for<'a>(auth: &'a HostAuth, url: &Url) -> Option<&'a [(HeaderName, HeaderValue)]> {
+    let host = url.host_str()?.to_lowercase();
+    auth.iter().find(|(h, _)| host == *h)
+        .or_else(|| auth.iter().find(|(h, _)| {
+            h.strip_prefix('.').is_some_and(|d| host == d || host.ends_with(h.as_str()))
+        }))
+        .map(|(_, pairs)| pairs.as_slice())
+}
+
+// --host-timeout override for this URL's host (most specific entry wins), else None so the
+// client's --timeout-ms applies.
+fn host_timeout(url: &Url, overrides: &[(String, u64)]) -> Option<Duration> {
+    let host = url.host_str()?.to_lowercase();
+    let host = host.trim_start_matches("www.");
+    overrides.iter()
+        .filter(|(h, _)| host == h || host.ends_with(&format!(".{}", h)))
+        .max_by_key(|(h, _)| h.len())
+        .map(|(_, ms)| Duration::from_millis(*ms))
+}
+
+// A reusable browser session from --session-file:
+// {"user_agent": "...", "cookies": [{"name": "sid", "value": "...", "domain": "example.com"}], "headers": {"X-Foo": "bar"}}
+// Cookies without a domain, and the headers, go only to the start URL's host.
+struct Session {
+    user_agent: Option<String>,
+    // (name, value, domain); a domain also covers its subdomains
+    cookies: Vec<(String, String, Option<String>)>,
+    headers: Vec<(HeaderName, HeaderValue)>,
+    // the start URL's host without "www.", so a www redirect stays in scope
+    home: String,
+}
+
+static SESSION: OnceCell<Session> = OnceCell::new();
+
+fn load_session(path: &Path, start: &Url) -> Result<Session, Box<dyn Error>> {
+    let json: JsonValue = serde_json::from_str(&fs::read_to_string(path)?)?;
+    let obj = json.as_object().ok_or("expected a JSON object")?;
+    if let Some(key) = obj.keys().find(|k| !["user_agent", "cookies", "headers"].contains(&k.as_str())) {
+        return Err(format!("unknown key {:?} (expected user_agent, cookies, headers)", key).into());
+    }
+    let user_agent = match obj.get("user_agent") {
+        None => None,
+        Some(v) => Some(v.as_str().ok_or("user_agent must be a string")?.to_string()),
+    };
+    let mut cookies = Vec::new();
+    if let Some(v) = obj.get("cookies") {
+        for c in v.as_array().ok_or("cookies must be an array")? {
+            let name = c.get("name").and_then(|n| n.as_str()).ok_or("each cookie needs a string \"name\"")?;
+            let value = c.get("value").and_then(|n| n.as_str()).ok_or("each cookie needs a string \"value\"")?;
+            let domain = c.get("domain").and_then(|d| d.as_str())
+                .map(|d| d.trim_start_matches('.').to_lowercase())
+                .filter(|d| !d.is_empty());
+            cookies.push((name.to_string(), value.to_string(), domain));
+        }
+    }
+    let mut headers = Vec::new();
+    if let Some(v) = obj.get("headers") {
+        for (name, value) in v.as_object().ok_or("headers must be an object")? {
+            let value = value.as_str().ok_or_else(|| format!("header {} must be a string", name))?;
+            headers.push((HeaderName::from_bytes(name.as_bytes())?, HeaderValue::from_str(value)?));
+        }
+    }
+    let home = start.host_str().unwrap_or_default().to_lowercase().trim_start_matches("www.").to_string();
+    Ok(Session { user_agent, cookies, headers, home })
+}
+
+fn on_session_host(session: &Session, host: &str) -> bool {
+    host.trim_start_matches("www.") == session.home
+}
+
+// Session headers for this URL: only the start host gets them.
+fn session_headers<'s>(session: &'s Session, url: &Url) -> &'s [(HeaderName, HeaderValue)] {
+    let host = url.host_str().unwrap_or_default().to_lowercase();
+    if on_session_host(session, &host) { &session.headers } else { &[] }
+}
+
+// Cookie header for this URL from the loaded session, if any cookies apply.
+fn session_cookie_header(session: &Session, url: &Url) -> Option<String> {
+    let host = url.host_str()?.to_lowercase();
+    let pairs: Vec<String> = session.cookies.iter()
+        .filter(|(_, _, domain)| match domain {
+            Some(d) => host == *d || host.ends_with(&format!(".{}", d)),
+            None => on_session_host(session, &host),
+        })
+        .map(|(name, value, _)| format!("{}={}", name, value))
+        .collect();
+    if pairs.is_empty() { None } else { Some(pairs.join("; ")) }
+}
+
 // Pick a random common browser user-agent string
+const USER_AGENTS: &[&str] = &[
+    "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36",
+    "Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/117.0 Safari/537.36",
+    "Mozilla/5.0 (Macintosh; Intel Mac OS X 13_6) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/16.6 Safari/605.1.15",
+    "Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:120.0) Gecko/20100101 Firefox/120.0",
+    "Mozilla/5.0 (iPhone; CPU iPhone OS 17_0 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.0 Mobile/15E148 Safari/604.1",
+];
+
 fn pick_user_agent() -> String {
-    let agents = [
-        "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36",
-        "Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/117.0 Safari/537.36",
-        "Mozilla/5.0 (Macintosh; Intel Mac OS X 13_6) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/16.6 Safari/605.1.15",
-        "Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:120.0) Gecko/20100101 Firefox/120.0",
-        "Mozilla/5.0 (iPhone; CPU iPhone OS 17_0 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.0 Mobile/15E148 Safari/604.1",
+    USER_AGENTS.choose(&mut *rng()).unwrap_or(&USER_AGENTS[0]).to_string()
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum BrowserFamily {
+    Chrome,
+    Firefox,
+    Safari,
+}
+
+// Browser behind a User-Agent string (Chromium-based browsers count as Chrome).
+fn ua_family(ua: &str) -> BrowserFamily {
+    if ua.contains("Firefox/") {
+        BrowserFamily::Firefox
+    } else if ua.contains("Chrome/") || ua.contains("CriOS/") {
+        BrowserFamily::Chrome
+    } else {
+        BrowserFamily::Safari
+    }
+}
+
+const ACCEPT_LANGUAGES: &[&str] = &["en-US,en;q=0.9", "en-GB,en;q=0.9", "en-US,en;q=0.8", "en-US,en;q=0.9,de;q=0.7"];
+
+// --realistic-headers: the navigation headers the UA's browser family actually sends, so the
+// request doesn't pair a Firefox User-Agent with Chrome's Accept (or client hints) and vice versa.
+fn browser_headers(ua: &str) -> Vec<(HeaderName, String)> {
+    let family = ua_family(ua);
+    let accept = match family {
+        BrowserFamily::Chrome => "text/html,application/xhtml+xml,application/xml;q=0.9,image/avif,image/webp,image/apng,*/*;q=0.8,application/signed-exchange;v=b3;q=0.7",
+        BrowserFamily::Firefox => "text/html,application/xhtml+xml,application/xml;q=0.9,image/avif,image/webp,*/*;q=0.8",
+        BrowserFamily::Safari => "text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8",
+    };
+    let language = ACCEPT_LANGUAGES.choose(&mut *rng()).copied().unwrap_or(ACCEPT_LANGUAGES[0]);
+    let mut headers = vec![
+        (ACCEPT, accept.to_string()),
+        (ACCEPT_LANGUAGE, language.to_string()),
+        (HeaderName::from_static("sec-fetch-dest"), "document".to_string()),
+        (HeaderName::from_static("sec-fetch-mode"), "navigate".to_string()),
+        (HeaderName::from_static("sec-fetch-site"), "none".to_string()),
+        (HeaderName::from_static("sec-fetch-user"), "?1".to_string()),
+        (HeaderName::from_static("upgrade-insecure-requests"), "1".to_string()),
     ];
-    let mut rng = thread_rng();
-    agents.choose(&mut rng).unwrap_or(&agents[0]).to_string()
+    // client hints are Chromium-only
+    if family == BrowserFamily::Chrome {
+        let major = ua.split("Chrome/").nth(1).and_then(|v| v.split('.').next()).unwrap_or("120");
+        let platform = if ua.contains("Windows") { "Windows" } else if ua.contains("Mac OS") { "macOS" } else { "Linux" };
+        headers.push((HeaderName::from_static("sec-ch-ua"), format!(r#""Chromium";v="{0}", "Google Chrome";v="{0}", "Not?A_Brand";v="99""#, major)));
+        headers.push((HeaderName::from_static("sec-ch-ua-mobile"), "?0".to_string()));
+        headers.push((HeaderName::from_static("sec-ch-ua-platform"), format!("\"{}\"", platform)));
+    }
+    headers
 }
 
-// Sleep a small randomized amount to mimic human browsing (200-600ms)
+// Sleep a small randomized amount to mimic human browsing (--politeness; 200-600ms by default)
 fn maybe_sleep() {
-    let mut rng = thread_rng();
-    let ms = rng.gen_range(200..=600);
-    sleep(Duration::from_millis(ms));
+    let Pacing { delay_ms, jitter_ms, .. } = pacing();
+    let ms = delay_ms + rng().gen_range(0..=jitter_ms);
+    if ms > 0 { sleep(Duration::from_millis(ms)); }
+}
+
+// The one RNG behind every random choice: seeded from --seed when given, else from entropy.
+static RNG: Lazy<Mutex<StdRng>> = Lazy::new(|| {
+    Mutex::new(match opts().seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    })
+});
+
+fn rng() -> std::sync::MutexGuard<'static, StdRng> {
+    RNG.lock().unwrap()
 }
 
 // Heuristic URL-level paywall checks (avoid fetching if URL strongly indicates paywall)
@@ -430,80 +2531,520 @@ fn is_paywalled_page(_document: &Html) -> bool {
     false
 }
 
+// Source of page bodies for the crawl. The real implementation wraps the HTTP client;
+// the extraction functions only see this trait so they can run against canned pages.
+trait Fetcher {
+    fn get(&self, url: &Url) -> Result<Page, Box<dyn Error>>;
+    fn head(&self, url: &Url) -> Result<Head, Box<dyn Error>>;
+}
+
+// Status, declared Content-Type and Content-Length from a HEAD request.
+struct Head {
+    status: u16,
+    content_type: Option<String>,
+    content_length: Option<u64>,
+}
+
+// A fetched page: the final URL after redirects, its body text and declared Content-Type.
+struct Page {
+    url: Url,
+    body: String,
+    content_type: Option<String>,
+}
+
+impl Page {
+    // HTML/XHTML, or undeclared (many servers omit the header on real pages)
+    fn is_html(&self) -> bool {
+        match &self.content_type {
+            None => true,
+            Some(ct) => ct.contains("text/html") || ct.contains("application/xhtml"),
+        }
+    }
+
+    // RSS/Atom/RDF by declared type, or by the root element of an XML body
+    fn is_feed(&self) -> bool {
+        let ct = self.content_type.as_deref().unwrap_or_default();
+        if ["rss+xml", "atom+xml", "rdf+xml"].iter().any(|t| ct.contains(t)) { return true; }
+        if ct.contains("html") { return false; }
+        let head: String = self.body.trim_start_matches('\u{feff}').chars().take(2048).collect();
+        RE_FEED_ROOT.is_match(&head)
+    }
+}
+
+// Root element after an optional XML declaration, comments, stylesheet PIs and doctype.
+static RE_FEED_ROOT: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^\s*(?:<\?[^>]*\?>\s*|<!--(?s:.*?)-->\s*|<!DOCTYPE[^>]*>\s*)*<(?:rss|feed|rdf:RDF)[\s>]").unwrap()
+});
+
+// Content types worth downloading as text: HTML plus the XML/JSON used by feeds and structured data.
+fn is_textual_content_type(ct: &str) -> bool {
+    ct.starts_with("text/") || ct.contains("xml") || ct.contains("json")
+}
+
+// Structured fetch failures the crawl reacts to (rate limiting in particular).
+#[derive(Debug)]
+enum FetchError {
+    Status { status: reqwest::StatusCode, retry_after: Option<Duration> },
+    // the host kept answering 429 and further requests to it are skipped
+    HostThrottled(String),
+    // binary/non-text response (PDF, image, ...) whose body was not downloaded
+    Unsupported(String),
+    // the URL was already fetched earlier in this run
+    AlreadyFetched(String),
+    // --max-total-bytes is spent
+    BudgetExhausted(usize),
+}
+
+impl std::fmt::Display for FetchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FetchError::Status { status, .. } => write!(f, "HTTP error: {}", status),
+            FetchError::HostThrottled(host) => write!(f, "giving up on {} after repeated 429 responses", host),
+            FetchError::Unsupported(ct) => write!(f, "unsupported content type: {}", ct),
+            FetchError::AlreadyFetched(url) => write!(f, "already fetched in this run: {}", url),
+            FetchError::BudgetExhausted(limit) => write!(f, "download budget of {} bytes spent", limit),
+        }
+    }
+}
+
+impl Error for FetchError {}
+
+// 429s from one host before we stop requesting it, and the longest Retry-After we honour.
+const MAX_429_PER_HOST: u32 = 3;
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(60);
+
+struct HttpFetcher<'a> {
+    client: &'a Client,
+    timeout_ms: u64,
+    // 429 responses seen per host
+    throttled: Mutex<HashMap<String, u32>>,
+}
+
+impl<'a> HttpFetcher<'a> {
+    fn new(client: &'a Client, timeout_ms: u64) -> Self {
+        HttpFetcher { client, timeout_ms, throttled: Mutex::new(HashMap::new()) }
+    }
+}
+
+impl Fetcher for HttpFetcher<'_> {
+    // On 429, pause for Retry-After (capped) and try again; after repeated 429s abandon the host.
+    fn get(&self, url: &Url) -> Result<Page, Box<dyn Error>> {
+        let host = url.host_str().unwrap_or_default().to_lowercase();
+        loop {
+            if self.throttled.lock().unwrap().get(&host).copied().unwrap_or(0) >= MAX_429_PER_HOST {
+                return Err(FetchError::HostThrottled(host).into());
+            }
+            let err = match get_text_with_headers(self.client, url, self.timeout_ms) {
+                Ok(page) => {
+                    STATS.pages.fetch_add(1, Ordering::Relaxed);
+                    STATS.bytes.fetch_add(page.body.len(), Ordering::Relaxed);
+                    return Ok(page);
+                }
+                Err(e) => {
+                    STATS.failures.fetch_add(1, Ordering::Relaxed);
+                    e
+                }
+            };
+            let retry_after = match err.downcast_ref::<FetchError>() {
+                Some(FetchError::Status { status, retry_after }) if *status == reqwest::StatusCode::TOO_MANY_REQUESTS => *retry_after,
+                _ => return Err(err),
+            };
+            let seen = {
+                let mut map = self.throttled.lock().unwrap();
+                let n = map.entry(host.clone()).or_insert(0);
+                *n += 1;
+                *n
+            };
+            if seen >= MAX_429_PER_HOST || interrupted() {
+                diag!("Rate limited by {} {} times; skipping further fetches to it", host, seen);
+                return Err(FetchError::HostThrottled(host).into());
+            }
+            let wait = retry_after.unwrap_or(Duration::from_secs(5)).min(MAX_RETRY_AFTER);
+            diag!("Rate limited by {} (429); pausing {:?} before retrying {}", host, wait, url);
+            sleep(wait);
+        }
+    }
+
+    fn head(&self, url: &Url) -> Result<Head, Box<dyn Error>> {
+        maybe_sleep();
+        let resp = request_with_headers(self.client, reqwest::Method::HEAD, url).send()?;
+        let content_type = resp.headers().get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.to_lowercase());
+        Ok(Head { status: resp.status().as_u16(), content_type, content_length: resp.content_length() })
+    }
+}
+
+// Meta refreshes with a longer delay are "reload this page" timers, not redirects.
+const MAX_META_REFRESH_DELAY: u64 = 5;
+// Upper bound on chained meta-refresh hops, so refresh loops terminate.
+const MAX_META_REFRESH_HOPS: usize = 3;
+
+static RE_META_REFRESH: Lazy<Regex> = Lazy::new(|| Regex::new(r#"(?i)^\s*(\d+)(?:\.\d*)?\s*(?:[;,]\s*(?:url\s*=\s*)?['"]?([^'"]*)['"]?)?"#).unwrap());
+
+// Target of a short-delay <meta http-equiv="refresh" content="0;url=..."> on an HTML page.
+fn meta_refresh_target(page: &Page) -> Option<Url> {
+    if !page.is_html() || !page.body.contains("efresh") { return None; }
+    let doc = Html::parse_document(&page.body);
+    let sel = Selector::parse("meta[http-equiv][content]").ok()?;
+    let content = doc.select(&sel)
+        .find(|m| m.value().attr("http-equiv").is_some_and(|v| v.trim().eq_ignore_ascii_case("refresh")))?
+        .value().attr("content")?;
+    let caps = RE_META_REFRESH.captures(content)?;
+    let delay: u64 = caps.get(1)?.as_str().parse().ok()?;
+    let target = caps.get(2).map(|m| m.as_str().trim()).filter(|t| !t.is_empty())?;
+    if delay > MAX_META_REFRESH_DELAY { return None; }
+    let url = page.url.join(target).ok()?;
+    if url == page.url || !matches!(url.scheme(), "http" | "https") { return None; }
+    Some(url)
+}
+
+// --concurrency-per-host: a counting semaphore per host around the HTTP fetcher, so fetches
+// from several threads spread across hosts without piling onto one. The crawl loops still fetch
+// on one thread, so a slot is always free and the limit has no effect until fetching is parallel.
+struct HostLimitFetcher<'a> {
+    inner: &'a (dyn Fetcher + Sync),
+    per_host: usize,
+    active: Mutex<HashMap<String, usize>>,
+    freed: Condvar,
+}
+
+// A held per-host slot, released on drop.
+struct HostSlot<'f, 'a> {
+    fetcher: &'f HostLimitFetcher<'a>,
+    host: String,
+}
+
+impl Drop for HostSlot<'_, '_> {
+    fn drop(&mut self) {
+        let mut active = self.fetcher.active.lock().unwrap();
+        if let Some(n) = active.get_mut(&self.host) {
+            *n -= 1;
+            if *n == 0 { active.remove(&self.host); }
+        }
+        self.fetcher.freed.notify_all();
+    }
+}
+
+impl<'a> HostLimitFetcher<'a> {
+    fn new(inner: &'a (dyn Fetcher + Sync), per_host: u32) -> Self {
+        HostLimitFetcher { inner, per_host: per_host.max(1) as usize, active: Mutex::new(HashMap::new()), freed: Condvar::new() }
+    }
+
+    fn acquire(&self, url: &Url) -> HostSlot<'_, 'a> {
+        let host = url.host_str().unwrap_or_default().to_lowercase();
+        let mut active = self.active.lock().unwrap();
+        while active.get(&host).copied().unwrap_or(0) >= self.per_host {
+            active = self.freed.wait(active).unwrap();
+        }
+        *active.entry(host.clone()).or_insert(0) += 1;
+        HostSlot { fetcher: self, host }
+    }
+}
+
+impl Fetcher for HostLimitFetcher<'_> {
+    fn get(&self, url: &Url) -> Result<Page, Box<dyn Error>> {
+        let _slot = self.acquire(url);
+        self.inner.get(url)
+    }
+
+    fn head(&self, url: &Url) -> Result<Head, Box<dyn Error>> {
+        let _slot = self.acquire(url);
+        self.inner.head(url)
+    }
+}
+
+// --max-total-bytes: counts body bytes across every GET and refuses further fetches once the
+// budget is spent (the page that crosses it is kept), so the crawl writes what it has.
+struct BudgetFetcher<'a> {
+    inner: &'a dyn Fetcher,
+    limit: usize,
+    used: AtomicUsize,
+    announced: AtomicBool,
+}
+
+impl<'a> BudgetFetcher<'a> {
+    fn new(inner: &'a dyn Fetcher, limit: Option<usize>) -> Self {
+        BudgetFetcher { inner, limit: limit.unwrap_or(usize::MAX), used: AtomicUsize::new(0), announced: AtomicBool::new(false) }
+    }
+}
+
+impl Fetcher for BudgetFetcher<'_> {
+    fn get(&self, url: &Url) -> Result<Page, Box<dyn Error>> {
+        if self.used.load(Ordering::Relaxed) >= self.limit {
+            if !self.announced.swap(true, Ordering::Relaxed) {
+                diag!("Download budget of {} KB spent; writing items collected so far", self.limit / 1024);
+            }
+            return Err(FetchError::BudgetExhausted(self.limit).into());
+        }
+        let page = self.inner.get(url)?;
+        self.used.fetch_add(page.body.len(), Ordering::Relaxed);
+        Ok(page)
+    }
+
+    fn head(&self, url: &Url) -> Result<Head, Box<dyn Error>> {
+        self.inner.head(url)
+    }
+}
+
+// Run-wide guard: refuses a second GET of any URL (canonicalized, and including where
+// redirects landed) however it was discovered. A failed fetch is forgotten so retries work.
+struct VisitedFetcher<'a> {
+    inner: &'a dyn Fetcher,
+    visited: Mutex<HashSet<String>>,
+}
+
+impl<'a> VisitedFetcher<'a> {
+    fn new(inner: &'a dyn Fetcher) -> Self {
+        VisitedFetcher { inner, visited: Mutex::new(HashSet::new()) }
+    }
+
+    // Treat URLs as fetched already (pages done in a previous run, per --checkpoint).
+    fn mark_visited<'u>(&self, urls: impl IntoIterator<Item = &'u String>) {
+        let mut visited = self.visited.lock().unwrap();
+        visited.extend(urls.into_iter().map(|u| canonicalize_url_str(u)));
+    }
+}
+
+impl Fetcher for VisitedFetcher<'_> {
+    fn get(&self, url: &Url) -> Result<Page, Box<dyn Error>> {
+        let key = canonicalize_url_str(url.as_str());
+        if !self.visited.lock().unwrap().insert(key.clone()) {
+            if verbose() { diag!("Already fetched, skipping: {}", url); }
+            return Err(FetchError::AlreadyFetched(url.to_string()).into());
+        }
+        match self.inner.get(url) {
+            Ok(page) => {
+                self.visited.lock().unwrap().insert(canonicalize_url_str(page.url.as_str()));
+                Ok(page)
+            }
+            Err(e) => {
+                self.visited.lock().unwrap().remove(&key);
+                Err(e)
+            }
+        }
+    }
+
+    fn head(&self, url: &Url) -> Result<Head, Box<dyn Error>> {
+        self.inner.head(url)
+    }
+}
+
+// Follows meta-refresh interstitials on top of another fetcher, returning the page they point at.
+struct MetaRefreshFetcher<'a> {
+    inner: &'a dyn Fetcher,
+}
+
+impl Fetcher for MetaRefreshFetcher<'_> {
+    fn get(&self, url: &Url) -> Result<Page, Box<dyn Error>> {
+        let mut page = self.inner.get(url)?;
+        if opts().no_meta_refresh { return Ok(page); }
+        for _ in 0..MAX_META_REFRESH_HOPS {
+            let Some(target) = meta_refresh_target(&page) else { break };
+            if verbose() { diag!("Following meta refresh {} -> {}", page.url, target); }
+            page = self.inner.get(&target)?;
+        }
+        Ok(page)
+    }
+
+    fn head(&self, url: &Url) -> Result<Head, Box<dyn Error>> {
+        self.inner.head(url)
+    }
+}
+
+// --head-first: whether a candidate is worth a GET. Servers that reject HEAD (405/501) or
+// fail it outright get the benefit of the doubt; otherwise require 200 and an HTML type.
+fn head_allows(fetcher: &dyn Fetcher, url: &Url) -> bool {
+    match fetcher.head(url) {
+        Ok(Head { status: 405 | 501, .. }) | Err(_) => true,
+        Ok(Head { status: 200, content_type, .. }) => {
+            Page { url: url.clone(), body: String::new(), content_type }.is_html()
+        }
+        Ok(Head { status, .. }) => {
+            if verbose() { diag!("Skipping candidate (HEAD {}): {}", status, url); }
+            false
+        }
+    }
+}
+
+// Crawl helper: treat a successfully fetched non-HTML page (JSON, XML, plain text) as unusable.
+trait FilterHtml {
+    fn filter_html(self) -> Result<Page, Box<dyn Error>>;
+}
+
+impl FilterHtml for Result<Page, Box<dyn Error>> {
+    fn filter_html(self) -> Result<Page, Box<dyn Error>> {
+        let page = self?;
+        if page.is_html() { return Ok(page); }
+        let ct = page.content_type.unwrap_or_default();
+        Err(FetchError::Unsupported(ct).into())
+    }
+}
+
+// Parse a Retry-After header: delay in seconds or an HTTP-date.
+fn parse_retry_after(value: &str) -> Option<Duration> {
+    let v = value.trim();
+    if let Ok(secs) = v.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let when = DateTime::parse_from_rfc2822(v).ok()?;
+    (when.with_timezone(&chrono::Utc) - chrono::Utc::now()).to_std().ok()
+}
+
+// A request carrying our browser-like headers, session (UA, headers, cookies) and per-host auth.
+fn request_with_headers(client: &Client, method: reqwest::Method, url: &Url) -> reqwest::blocking::RequestBuilder {
+    let session = SESSION.get();
+    let ua = session.and_then(|s| s.user_agent.clone()).unwrap_or_else(pick_user_agent);
+    let mut req = client.request(method, url.as_str());
+    if opts().realistic_headers {
+        for (name, value) in browser_headers(&ua) {
+            req = req.header(name, value);
+        }
+    } else {
+        req = req
+            .header(ACCEPT, "text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8")
+            .header(ACCEPT_LANGUAGE, "en-US,en;q=0.9");
+    }
+    let mut req = req
+        .header(USER_AGENT, ua)
+        .header(CONNECTION, "keep-alive");
+    if let Some(session) = session {
+        for (name, value) in session_headers(session, url) {
+            req = req.header(name.clone(), value.clone());
+        }
+        if let Some(cookie) = session_cookie_header(session, url) {
+            req = req.header(reqwest::header::COOKIE, cookie);
+        }
+    }
+    // credentials are scoped to their own host, even when a crawl crosses hosts
+    if let Some(pairs) = host_auth_headers(url) {
+        for (name, value) in pairs {
+            req = req.header(name.clone(), value.clone());
+        }
+    }
+    if let Some(timeout) = host_timeout(url, &opts().host_timeout) {
+        req = req.timeout(timeout);
+    }
+    req
+}
+
 // Fetch a URL's text while applying rotating headers, small randomized delay, and paywall checks.
-fn get_text_with_headers(client: &Client, url: &Url, _timeout_ms: u64) -> Result<String, Box<dyn Error>> {
+fn get_text_with_headers(client: &Client, url: &Url, _timeout_ms: u64) -> Result<Page, Box<dyn Error>> {
     // Avoid fetching clearly paywalled URLs
     if is_paywalled_url(url) {
-        eprintln!("Skipping paywalled URL (pattern): {}", url.as_str());
+        diag!("Skipping paywalled URL (pattern): {}", url.as_str());
         return Err("paywalled URL".into());
     }
 
     maybe_sleep();
 
-    let ua = pick_user_agent();
-    let resp = client
-        .get(url.as_str())
-        .header(USER_AGENT, ua)
-        .header(ACCEPT, "text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8")
-        .header(ACCEPT_LANGUAGE, "en-US,en;q=0.9")
-        .header(CONNECTION, "keep-alive")
-        .send()?;
+    let sent = Instant::now();
+    let resp = request_with_headers(client, reqwest::Method::GET, url).send()?;
+    if opts().verbose > 1 {
+        diag!("GET {} -> {} {:?} in {} ms", url, resp.status().as_u16(), resp.version(), sent.elapsed().as_millis());
+    }
 
     if !resp.status().is_success() {
-        return Err(format!("HTTP error: {}", resp.status()).into());
+        let retry_after = resp.headers().get(reqwest::header::RETRY_AFTER)
+            .and_then(|v| v.to_str().ok())
+            .and_then(parse_retry_after);
+        return Err(FetchError::Status { status: resp.status(), retry_after }.into());
     }
 
+    let final_url = resp.url().clone();
+    let content_type = resp.headers().get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.to_lowercase());
+    // Don't download PDFs, images and other binaries we can't extract from
+    if let Some(ct) = content_type.as_deref().filter(|ct| !is_textual_content_type(ct)) {
+        return Err(FetchError::Unsupported(ct.to_string()).into());
+    }
     let body = resp.text()?;
-    let doc = Html::parse_document(&body);
-    // Skip page-level paywall detection for allowed domains
-    if !allowed_domain(url) && is_paywalled_page(&doc) {
-        eprintln!("Skipping paywalled page detected after fetch: {}", url.as_str());
+    let page = Page { url: final_url, body, content_type };
+    // Skip page-level paywall detection for allowed domains (and non-HTML bodies such as feeds)
+    if page.is_html() && !allowed_domain(url) && is_paywalled_page(&Html::parse_document(&page.body)) {
+        diag!("Skipping paywalled page detected after fetch: {}", url.as_str());
         return Err("paywalled page".into());
     }
 
-    Ok(body)
+    Ok(page)
+}
+
+// Fill missing enclosure lengths with a HEAD request's Content-Length (--probe-enclosures).
+// Goes through the crawl's fetcher, so host limits, session and auth headers apply as for pages.
+// The card video is probed when there is one, since write_item makes it the enclosure.
+fn probe_enclosures(fetcher: &dyn Fetcher, items: &mut [Item]) {
+    for it in items.iter_mut() {
+        if interrupted() { continue; }
+        let (target, length) = match &it.video {
+            Some(video) => (video, &mut it.video_length),
+            None => match &it.image { Some(img) => (img, &mut it.image_length), None => continue },
+        };
+        if length.is_some() { continue; }
+        let Ok(target) = Url::parse(target) else { continue };
+        match fetcher.head(&target) {
+            Ok(head) if (200..300).contains(&head.status) => *length = head.content_length.filter(|n| *n > 0),
+            Ok(head) => diag!("Enclosure probe failed for {}: HTTP {}", target, head.status),
+            Err(e) => diag!("Enclosure probe failed for {}: {}", target, e),
+        }
+    }
 }
 
 // Fetch with retry logic and exponential backoff
-fn get_text_with_headers_retry(client: &Client, url: &Url, timeout_ms: u64, max_retries: u32) -> Result<String, Box<dyn Error>> {
+fn get_text_with_retry(fetcher: &dyn Fetcher, url: &Url, max_retries: u32) -> Result<Page, Box<dyn Error>> {
     let mut last_error = None;
     
     for attempt in 0..=max_retries {
+        if attempt > 0 && interrupted() { break; }
         if attempt > 0 {
             let backoff = Duration::from_millis(1000 * 2_u64.pow(attempt - 1));
             let backoff_capped = backoff.min(Duration::from_secs(10));
-            eprintln!("Retrying {} after {:?} (attempt {}/{})", url, backoff_capped, attempt + 1, max_retries + 1);
+            diag!("Retrying {} after {:?} (attempt {}/{})", url, backoff_capped, attempt + 1, max_retries + 1);
             sleep(backoff_capped);
         }
         
-        match get_text_with_headers(client, url, timeout_ms) {
-            Ok(body) => return Ok(body),
+        match fetcher.get(url) {
+            Ok(page) => return Ok(page),
+            // the fetcher already waited out rate limiting (or has this page); retrying won't help
+            Err(e) if matches!(e.downcast_ref::<FetchError>(), Some(FetchError::HostThrottled(_) | FetchError::AlreadyFetched(_) | FetchError::BudgetExhausted(_))) => return Err(e),
             Err(e) => {
                 if attempt < max_retries {
-                    eprintln!("Attempt {}/{} failed for {}: {}", attempt + 1, max_retries + 1, url, e);
+                    diag!("Attempt {}/{} failed for {}: {}", attempt + 1, max_retries + 1, url, e);
                 }
                 last_error = Some(e);
             }
         }
     }
     
-    Err(last_error.unwrap())
+    Err(last_error.unwrap_or_else(|| "interrupted".into()))
 }
 
 fn extract_from_html(
-    client: &Client,
+    fetcher: &dyn Fetcher,
     document: &Html,
     base: &Url,
     max_pages: usize,
-    timeout_ms: u64,
 ) -> Vec<Item> {
-    let mut items: Vec<Item> = Vec::new();
+    extract_from_html_with(fetcher, document, base, max_pages, ItemFilter::from_opts())
+}
+
+fn extract_from_html_with(
+    fetcher: &dyn Fetcher,
+    document: &Html,
+    base: &Url,
+    max_pages: usize,
+    filter: ItemFilter,
+) -> Vec<Item> {
+    // items found by an earlier, interrupted run (--checkpoint)
+    let mut items: Vec<Item> = checkpoint_items();
 
-    // 1) Extract local <article> elements
+    // 1) Extract local <article> elements; enough of them that survive the filters end the crawl
     extract_article_elements(document, base, max_pages, &mut items);
 
     if items.len() >= max_pages && !items.is_empty() {
-        return items;
+        filter_items_with(base, &mut items, filter);
+        if opts().dedupe_similar { dedupe_similar(&mut items, opts().similarity_threshold); }
+        if items.len() >= max_pages { return items; }
     }
 
     // 2) Extract related articles if page looks like a single article
@@ -515,10 +3056,11 @@ fn extract_from_html(
     let candidates = build_candidate_list(document, base, max_pages);
 
     // 4) Fetch and parse candidates
-    fetch_candidates(client, &candidates, base, max_pages, timeout_ms, &mut items);
+    fetch_candidates(fetcher, &candidates, base, max_pages, &mut items);
 
     // 5) Filter and deduplicate
-    filter_items(base, &mut items);
+    filter_items_with(base, &mut items, filter);
+    if opts().dedupe_similar { dedupe_similar(&mut items, opts().similarity_threshold); }
 
     items
 }
@@ -533,7 +3075,7 @@ fn extract_article_elements(document: &Html, base: &Url, max_pages: usize, items
             let title = art.select(&Selector::parse("h1,h2,h3").unwrap())
                 .next()
                 .and_then(|n| n.text().next())
-                .map(|s| fix_mojibake(&s.trim().to_string()));
+                .map(|s| clean_title(s.trim()));
 
             if let Some(title) = title {
                 if title.trim().is_empty() { continue; }
@@ -541,7 +3083,7 @@ fn extract_article_elements(document: &Html, base: &Url, max_pages: usize, items
                 let link = art.select(&Selector::parse("a").unwrap())
                     .next()
                     .and_then(|a| a.value().attr("href"))
-                    .and_then(|h| base.join(h).ok())
+                    .and_then(|h| base.join(&clean_href(h)).ok())
                     .map(|u| u.as_str().to_string())
                     .unwrap_or(base.as_str().to_string());
 
@@ -553,10 +3095,10 @@ fn extract_article_elements(document: &Html, base: &Url, max_pages: usize, items
 
                 if let Ok(link_url) = Url::parse(&link) {
                     if !is_blacklisted_url(&link_url) && !is_listing_page(&link_url, base) {
-                        items.push(Item { title, link, description: desc, pub_date: None, image: None });
+                        items.push(Item { title, link, description: desc, ..Default::default() });
                     }
                 } else {
-                    items.push(Item { title, link, description: desc, pub_date: None, image: None });
+                    items.push(Item { title, link, description: desc, ..Default::default() });
                 }
             }
         }
@@ -577,7 +3119,61 @@ fn looks_like_single_article(document: &Html) -> bool {
             }
         }
     }
-    false
+    // no article metadata: judge by the text itself
+    TextDensity::of(document).is_article()
+}
+
+// Text-density signals for pages without article metadata. Listings are mostly link text in
+// short teasers; articles have a long run of plain paragraphs under one parent.
+#[derive(Debug, Default, PartialEq)]
+struct TextDensity {
+    // visible characters (whitespace collapsed), and those inside links
+    text: usize,
+    link_text: usize,
+    // characters in the biggest group of sibling <p> elements
+    largest_block: usize,
+}
+
+// Thresholds: a few hundred words of running prose, and little of the page being links.
+const MIN_ARTICLE_BLOCK: usize = 1200;
+const MAX_ARTICLE_LINK_DENSITY: f64 = 0.35;
+
+impl TextDensity {
+    fn of(document: &Html) -> Self {
+        let mut density = TextDensity::default();
+        let skipped = ["script", "style", "noscript", "template"];
+        for node in document.root_element().descendants() {
+            let Some(text) = node.value().as_text() else { continue };
+            let mut in_link = false;
+            let mut hidden = false;
+            for anc in node.ancestors().filter_map(|a| a.value().as_element().map(|e| e.name())) {
+                in_link |= anc == "a";
+                hidden |= skipped.contains(&anc);
+            }
+            if hidden { continue; }
+            let len = text.split_whitespace().map(|w| w.chars().count() + 1).sum::<usize>();
+            density.text += len;
+            if in_link { density.link_text += len; }
+        }
+        if let Ok(sel_p) = Selector::parse("p") {
+            let mut blocks: HashMap<_, usize> = HashMap::new();
+            for p in document.select(&sel_p) {
+                let Some(parent) = p.parent() else { continue };
+                let len = p.text().flat_map(str::split_whitespace).map(|w| w.chars().count() + 1).sum::<usize>();
+                *blocks.entry(parent.id()).or_default() += len;
+            }
+            density.largest_block = blocks.into_values().max().unwrap_or(0);
+        }
+        density
+    }
+
+    fn link_density(&self) -> f64 {
+        if self.text == 0 { 1.0 } else { self.link_text as f64 / self.text as f64 }
+    }
+
+    fn is_article(&self) -> bool {
+        self.largest_block >= MIN_ARTICLE_BLOCK && self.link_density() <= MAX_ARTICLE_LINK_DENSITY
+    }
 }
 
 fn extract_related_articles(document: &Html, base: &Url, max_pages: usize, items: &mut Vec<Item>) {
@@ -592,14 +3188,14 @@ fn extract_related_articles(document: &Html, base: &Url, max_pages: usize, items
                 for a in node.select(&Selector::parse("a").unwrap()) {
                     if items.len() >= max_pages { break; }
                     if let Some(href) = a.value().attr("href") {
-                        if let Ok(abs) = base.join(href) {
+                        if let Ok(abs) = base.join(&clean_href(href)) {
                             if abs.domain() == base.domain() {
                                 let s = abs.as_str().to_string();
                                 if items.iter().any(|it| it.link == s) { continue; }
                                 if is_blacklisted_url(&abs) || is_listing_page(&abs, base) { continue; }
-                                let title = fix_mojibake(&a.text().collect::<Vec<_>>().join(" ").trim().to_string());
+                                let title = clean_title(a.text().collect::<Vec<_>>().join(" ").trim());
                                 if title.is_empty() || is_error_page(document, &title, &None) { continue; }
-                                items.push(Item { title, link: s, description: None, pub_date: None, image: None });
+                                items.push(Item { title, link: s, ..Default::default() });
                             }
                         }
                     }
@@ -615,90 +3211,304 @@ static RE_ARTICLE: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)(/article/|/artic
 static RE_HUFF_ENTRY: Lazy<Regex> = Lazy::new(|| Regex::new(r"/entry/[^/]+_[0-9]+$").unwrap());
 static RE_WHITESPACE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\s+").unwrap());
 
+// Runs of Symbol/Other characters and emoji, with the joiners, variation selectors, skin-tone
+// modifiers, keycap marks and tag characters that compose them. Latin-1 symbols (° © ®) stay.
+static RE_SYMBOLS: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"[[\p{So}\p{Extended_Pictographic}--\x00-\xFF]\p{Emoji_Modifier}\u{200D}\u{20E3}\u{FE0E}\u{FE0F}\u{E0020}-\u{E007F}]+").unwrap()
+});
+
+// --strip-symbols: drop emoji and pictographs, leaving the surrounding text readable.
+fn strip_symbols(s: &str, mode: SymbolMode) -> String {
+    let with = match mode {
+        SymbolMode::Remove => "",
+        SymbolMode::Space => " ",
+    };
+    let out = RE_SYMBOLS.replace_all(s, with);
+    RE_WHITESPACE.replace_all(out.trim(), " ").into_owned()
+}
+
 // Cap text elements to avoid enormous feed entries (truncate with ellipsis)
 const MAX_TEXT_LEN: usize = 4096;
 
+// With --use-noscript, parse <noscript> blocks (kept as raw text by the parser) into sub-documents.
+fn noscript_fragments(document: &Html) -> Vec<Html> {
+    if !opts().use_noscript { return Vec::new(); }
+    parse_noscript_blocks(document)
+}
+
+fn parse_noscript_blocks(document: &Html) -> Vec<Html> {
+    let Ok(sel) = Selector::parse("noscript") else { return Vec::new() };
+    document.select(&sel)
+        .map(|n| n.text().collect::<String>())
+        .filter(|inner| inner.contains('<'))
+        .map(|inner| Html::parse_fragment(&inner))
+        .collect()
+}
+
+// How article-like a link looks. Strong URL signals (date path, article/story segment) outweigh
+// presentation hints (long anchor text, thumbnail, card container); rel=nofollow counts against.
+fn candidate_score(a: &ElementRef, abs: &Url, base: &Url) -> i32 {
+    let s = abs.as_str();
+    let mut score = 0;
+    if RE_DATE.is_match(s) { score += 3; }
+    if RE_ARTICLE.is_match(s) { score += 3; }
+    let link_text = fix_mojibake(a.text().collect::<Vec<_>>().join(" ").trim());
+    if link_text.len() > 25 { score += 2; }
+    if a.select(&Selector::parse("img").unwrap()).next().is_some() { score += 1; }
+
+    let mut p = a.parent(); let mut depth = 0;
+    while let Some(parent_node) = p {
+        if let Some(elem) = ElementRef::wrap(parent_node) {
+            if let Some(class_attr) = elem.value().attr("class") {
+                let cls = class_attr.to_lowercase();
+                if cls.contains("card") || cls.contains("teaser") || cls.contains("promo") ||
+                   cls.contains("headline") || cls.contains("story") || cls.contains("article") {
+                    score += 1; break;
+                }
+            }
+        }
+        p = p.and_then(|n| n.parent()); depth += 1; if depth >= 4 { break; }
+    }
+
+    if a.value().attr("rel").is_some_and(|r| r.to_lowercase().split_whitespace().any(|t| t == "nofollow")) {
+        score -= 2;
+    }
+    if let Some(host) = base.host_str() {
+        if host.to_lowercase().contains("huffpost") {
+            if RE_HUFF_ENTRY.is_match(s) { score += 3; }
+            else if s.ends_with("/news") || s.ends_with("/news/") || s.ends_with("/all") { return i32::MIN; }
+        }
+    }
+    score
+}
+
+// Whole path segments: "/world" covers "/world" and "/world/europe/..." but not "/worldcup".
+fn path_has_prefix(u: &Url, prefix: &str) -> bool {
+    let dir = prefix.trim_end_matches('/');
+    let path = u.path();
+    path == dir || path.strip_prefix(dir).is_some_and(|rest| rest.starts_with('/'))
+}
+
+// --path-prefix: whether a URL lies in the section being crawled.
+fn in_crawl_scope(u: &Url) -> bool {
+    opts().path_prefix.as_deref().is_none_or(|p| path_has_prefix(u, p))
+}
+
+// Whitespace runs that contain a line break or tab: template indentation, never part of a URL.
+static RE_HREF_BREAK: Lazy<Regex> = Lazy::new(|| Regex::new(r"[ \t]*[\r\n\t][\s]*").unwrap());
+
+// An href attribute ready for Url::join: trimmed and without template line breaks, which the
+// URL parser would otherwise turn into "%20%20..." and break dedup.
+fn clean_href(href: &str) -> Cow<'_, str> {
+    let href = href.trim();
+    let collapse = OPTIONS.get().is_some_and(|a| a.collapse_whitespace_in_links);
+    let dirty = href.contains(['\r', '\n', '\t']) || (collapse && href.contains(' '));
+    if !dirty { return Cow::Borrowed(href); }
+    let mut out = RE_HREF_BREAK.replace_all(href, "").into_owned();
+    if collapse { out.retain(|c| !c.is_whitespace()); }
+    Cow::Owned(out)
+}
+
+// Same-domain links scoring at least --min-score, best first (document order among ties),
+// so a small --max-pages budget is spent on the strongest candidates.
 fn build_candidate_list(document: &Html, base: &Url, max_pages: usize) -> Vec<Url> {
+    let noscript = noscript_fragments(document);
+    build_candidate_list_in(document, &noscript, base, max_pages, opts().path_prefix.as_deref())
+}
+
+// `noscript`: the page's parsed <noscript> blocks, whose links count like any other.
+fn build_candidate_list_in(document: &Html, noscript: &[Html], base: &Url, max_pages: usize, prefix: Option<&str>) -> Vec<Url> {
     let mut seen = HashSet::new();
-    let mut candidates: Vec<Url> = Vec::new();
+    let mut scored: Vec<(i32, Url)> = Vec::new();
 
     if let Ok(sel_a) = Selector::parse("a") {
-        for a in document.select(&sel_a).take(2000) {
+        let anchors = document.select(&sel_a).take(2000).chain(noscript.iter().flat_map(|d| d.select(&sel_a)));
+        for a in anchors {
             if let Some(href) = a.value().attr("href") {
-                if let Ok(abs) = base.join(href) {
-                    if abs.domain() != base.domain() { continue; }
+                if let Ok(abs) = base.join(&clean_href(href)) {
+                    if abs.domain() != base.domain() || prefix.is_some_and(|p| !path_has_prefix(&abs, p)) { continue; }
                     let s = abs.as_str().to_string();
-                    if seen.contains(&s) { continue; }
-
-                    let link_text = fix_mojibake(&a.text().collect::<Vec<_>>().join(" ").trim().to_string());
-                    let has_img = a.select(&Selector::parse("img").unwrap()).next().is_some();
-                    let mut parent_is_card = false;
-                    let mut p = a.parent(); let mut depth = 0;
-                    while let Some(parent_node) = p {
-                        if let Some(elem) = ElementRef::wrap(parent_node) {
-                            if let Some(class_attr) = elem.value().attr("class") {
-                                let cls = class_attr.to_lowercase();
-                                if cls.contains("card") || cls.contains("teaser") || cls.contains("promo") ||
-                                   cls.contains("headline") || cls.contains("story") || cls.contains("article") {
-                                    parent_is_card = true; break;
-                                }
-                            }
-                        }
-                        p = p.and_then(|n| n.parent()); depth += 1; if depth >= 4 { break; }
-                    }
-
-                    let mut is_article_like = RE_DATE.is_match(&s) || RE_ARTICLE.is_match(&s) || link_text.len() > 25 || has_img || parent_is_card;
-                    if let Some(host) = base.host_str() {
-                        if host.to_lowercase().contains("huffpost") {
-                            if RE_HUFF_ENTRY.is_match(&s) { is_article_like = true; }
-                            else if s.ends_with("/news") || s.ends_with("/news/") || s.ends_with("/all") { is_article_like = false; }
-                        }
-                    }
+                    if seen.contains(&s) || is_blacklisted_url(&abs) { continue; }
 
-                    if is_article_like && !is_blacklisted_url(&abs) {
-                        seen.insert(s.clone());
-                        candidates.push(abs);
-                        if candidates.len() >= max_pages { break; }
+                    let score = candidate_score(&a, &abs, base);
+                    if score >= opts().min_score {
+                        seen.insert(s);
+                        scored.push((score, abs));
                     }
                 }
             }
         }
     }
 
-    candidates
+    scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+    scored.into_iter().take(max_pages).map(|(_, u)| u).collect()
 }
 
 fn fetch_candidates(
-    client: &Client,
+    fetcher: &dyn Fetcher,
     candidates: &[Url],
     base: &Url,
     max_pages: usize,
-    timeout_ms: u64,
     items: &mut Vec<Item>,
 ) {
     for cand in candidates.iter() {
-        if items.len() >= max_pages { break; }
+        if items.len() >= max_pages || interrupted() { break; }
+        if opts().head_first && !head_allows(fetcher, cand) { continue; }
 
         if is_listing_page(cand, base) {
-            if is_paywalled_url(cand) { eprintln!("Skipping listing URL (paywalled): {}", cand.as_str()); continue; }
-            if let Ok(text_list) = get_text_with_headers(client, cand, timeout_ms) {
-                let doc_list = Html::parse_document(&text_list);
-                extract_from_listing_page(client, &doc_list, cand, base, max_pages, items);
+            if is_paywalled_url(cand) { diag!("Skipping listing URL (paywalled): {}", cand.as_str()); continue; }
+            if let Ok(page) = fetcher.get(cand).filter_html() {
+                let doc_list = Html::parse_document(&page.body);
+                extract_from_listing_page(fetcher, &doc_list, &page.url, base, max_pages, items);
+                // a listing left part-way is walked again on resume
+                if items.len() < max_pages && !interrupted() { checkpoint_progress(cand, items); }
             }
             continue;
         }
 
         // Non-listing candidate: fetch directly
-        if is_paywalled_url(cand) { eprintln!("Skipping candidate URL (paywalled): {}", cand.as_str()); continue; }
-        if let Ok(text) = get_text_with_headers(client, cand, timeout_ms) {
-            let doc = Html::parse_document(&text);
-            extract_item_from_doc(&doc, cand, base, items);
+        if is_paywalled_url(cand) { diag!("Skipping candidate URL (paywalled): {}", cand.as_str()); continue; }
+        if let Ok(page) = fetcher.get(cand).filter_html() {
+            let doc = Html::parse_document(&page.body);
+            let before = items.len();
+            extract_item_from_doc(&doc, &page.url, base, items);
+            note_original_link(&mut items[before..], cand);
+            if opts().read_more { expand_read_more(fetcher, &doc, &page.url, &mut items[before..]); }
+            if opts().use_oembed { enrich_from_oembed(fetcher, &doc, &page.url, &mut items[before..]); }
+            checkpoint_progress(cand, items);
+        }
+        stream_found_items(base, items);
+    }
+}
+
+static RE_READ_MORE: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)\b(read|load|show) (the )?(more|full|rest)|continue reading").unwrap());
+
+// Same-host URL of the fragment a teaser page loads its body from: a data-article-url /
+// data-fragment(-url) attribute, or a "read more" link into an API/fragment/JSON path.
+fn read_more_url(doc: &Html, page_url: &Url) -> Option<Url> {
+    let same_host = |raw: &str| page_url.join(raw.trim()).ok()
+        .filter(|u| u.host_str() == page_url.host_str() && u != page_url);
+    let sel = Selector::parse("[data-article-url], [data-fragment], [data-fragment-url]").ok()?;
+    let from_attr = doc.select(&sel).find_map(|n| {
+        let v = n.value();
+        v.attr("data-article-url").or_else(|| v.attr("data-fragment-url")).or_else(|| v.attr("data-fragment")).and_then(same_host)
+    });
+    from_attr.or_else(|| {
+        let sel_a = Selector::parse("a[href]").ok()?;
+        doc.select(&sel_a)
+            .filter(|a| RE_READ_MORE.is_match(&a.text().collect::<String>()))
+            .filter_map(|a| a.value().attr("href").and_then(same_host))
+            .find(|u| {
+                let path = u.path().to_lowercase();
+                path.contains("/api/") || path.contains("fragment") || path.contains("ajax") || path.ends_with(".json")
+            })
+    })
+}
+
+// Plain text of a fetched fragment: an HTML snippet, or JSON carrying one under a usual key.
+fn fragment_text(body: &str) -> Option<String> {
+    let html = match serde_json::from_str::<JsonValue>(body) {
+        Ok(json) => {
+            let holder = json.get("data").filter(|d| d.is_object()).unwrap_or(&json);
+            ["html", "content", "body", "text"].iter()
+                .find_map(|k| holder.get(*k).and_then(|v| v.as_str()))?
+                .to_string()
+        }
+        Err(_) => body.to_string(),
+    };
+    let text = fix_mojibake(&sanitized_text(&Html::parse_fragment(&html), &opts().strip_selector));
+    (!text.is_empty()).then_some(text)
+}
+
+// Text of a fetched article fragment without the subtrees matching any --strip-selector.
+fn sanitized_text(doc: &Html, strip: &[String]) -> String {
+    let stripped: HashSet<_> = strip.iter()
+        .filter_map(|css| Selector::parse(css).ok())
+        .flat_map(|sel| doc.select(&sel).map(|el| el.id()).collect::<Vec<_>>())
+        .collect();
+    doc.root_element().descendants()
+        .filter_map(|node| node.value().as_text().map(|t| (node, t)))
+        .filter(|(node, _)| !node.ancestors().any(|a| stripped.contains(&a.id())))
+        .map(|(_, t)| t.trim())
+        .filter(|t| !t.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// --read-more: append the fragment's text to the teaser description of items from this page.
+fn expand_read_more(fetcher: &dyn Fetcher, doc: &Html, page_url: &Url, items: &mut [Item]) {
+    if items.is_empty() { return; }
+    let Some(frag_url) = read_more_url(doc, page_url) else { return };
+    let text = match fetcher.get(&frag_url) {
+        Ok(page) => fragment_text(&page.body),
+        Err(e) => {
+            if verbose() { diag!("Read-more fragment {} failed: {}", frag_url, e); }
+            None
+        }
+    };
+    let Some(text) = text else { return };
+    for it in items {
+        it.description = Some(match it.description.take() {
+            Some(teaser) if !text.starts_with(teaser.trim_end_matches('…')) => format!("{} {}", teaser, text),
+            _ => text.clone(),
+        });
+    }
+}
+
+// The page's oEmbed JSON endpoint, from <link rel="alternate" type="application/json+oembed">.
+fn oembed_url(doc: &Html, page_url: &Url) -> Option<Url> {
+    let sel = Selector::parse(r#"link[type="application/json+oembed"][href]"#).ok()?;
+    doc.select(&sel)
+        .filter_map(|l| page_url.join(l.value().attr("href")?.trim()).ok())
+        .find(|u| matches!(u.scheme(), "http" | "https"))
+}
+
+// --use-oembed: the provider's title and thumbnail replace scraped ones; author_name and the
+// text of its html fill gaps. Best-effort: without an endpoint, or on failure, items are kept as is.
+fn enrich_from_oembed(fetcher: &dyn Fetcher, doc: &Html, page_url: &Url, items: &mut [Item]) {
+    if items.is_empty() { return; }
+    let Some(endpoint) = oembed_url(doc, page_url) else { return };
+    let data = match fetcher.get(&endpoint).map(|p| serde_json::from_str::<JsonValue>(&p.body)) {
+        Ok(Ok(data)) if data.is_object() => data,
+        Ok(_) => {
+            if verbose() { diag!("oEmbed response from {} is not a JSON object", endpoint); }
+            return;
+        }
+        Err(e) => {
+            if verbose() { diag!("oEmbed request {} failed: {}", endpoint, e); }
+            return;
+        }
+    };
+    let text = |key: &str| data.get(key).and_then(|v| v.as_str()).map(str::trim).filter(|s| !s.is_empty());
+    let title = text("title").map(clean_title);
+    let author = text("author_name").map(fix_mojibake);
+    let thumbnail = text("thumbnail_url").and_then(|u| normalize_maybe_url(page_url, u));
+    let html_text = text("html")
+        .map(|h| fix_mojibake(&Html::parse_fragment(h).root_element().text().collect::<Vec<_>>().join(" ")))
+        .filter(|t| !t.is_empty());
+    for it in items {
+        if let Some(t) = &title { it.title = t.clone(); }
+        if let Some(img) = &thumbnail {
+            it.images.retain(|u| u != img);
+            it.images.insert(0, img.clone());
+            it.image = Some(img.clone());
+        }
+        if it.author.is_none() { it.author = author.clone(); }
+        if it.description.is_none() { it.description = html_text.clone(); }
+    }
+}
+
+// Record the URL we requested on items whose link ended up elsewhere (redirect, meta refresh).
+fn note_original_link(items: &mut [Item], requested: &Url) {
+    for it in items {
+        if it.original_link.is_none() && it.link != requested.as_str() {
+            it.original_link = Some(requested.to_string());
         }
     }
 }
 
 fn extract_from_listing_page(
-    client: &Client,
+    fetcher: &dyn Fetcher,
     doc_list: &Html,
     cand: &Url,
     base: &Url,
@@ -707,18 +3517,24 @@ fn extract_from_listing_page(
 ) {
     if let Ok(sel_a) = Selector::parse("a") {
         for a in doc_list.select(&sel_a) {
-            if items.len() >= max_pages { break; }
+            if items.len() >= max_pages || interrupted() { break; }
             if let Some(href) = a.value().attr("href") {
-                if let Ok(abs) = cand.join(href) {
-                    if abs.domain() != base.domain() { continue; }
+                if let Ok(abs) = cand.join(&clean_href(href)) {
+                    if abs.domain() != base.domain() || !in_crawl_scope(&abs) { continue; }
                     if items.iter().any(|it| it.link == abs.as_str()) { continue; }
 
                     let is_article_candidate = RE_DATE.is_match(abs.as_str()) || RE_ARTICLE.is_match(abs.as_str()) || a.select(&Selector::parse("img").unwrap()).next().is_some();
                     if is_article_candidate {
-                        if let Ok(text) = get_text_with_headers_retry(client, &abs, 10000, 2) {
-                            let doc = Html::parse_document(&text);
-                            extract_item_from_doc(&doc, &abs, base, items);
+                        if let Ok(page) = get_text_with_retry(fetcher, &abs, pacing().retries).filter_html() {
+                            let doc = Html::parse_document(&page.body);
+                            let before = items.len();
+                            extract_item_from_doc(&doc, &page.url, base, items);
+                            note_original_link(&mut items[before..], &abs);
+                            if opts().read_more { expand_read_more(fetcher, &doc, &page.url, &mut items[before..]); }
+                            if opts().use_oembed { enrich_from_oembed(fetcher, &doc, &page.url, &mut items[before..]); }
+                            checkpoint_progress(&abs, items);
                         }
+                        stream_found_items(base, items);
                     }
                 }
             }
@@ -726,38 +3542,110 @@ fn extract_from_listing_page(
     }
 }
 
-fn extract_item_from_doc(doc: &Html, cand: &Url, base: &Url, items: &mut Vec<Item>) {
-    if let Some(mut jitems) = extract_from_json_ld(doc, cand) {
-        if let Some(mut it) = jitems.pop() {
-            if it.link.is_empty() { it.link = cand.as_str().to_string(); }
-            if !is_error_page(doc, &it.title, &it.description) {
-                if let Ok(url) = Url::parse(&it.link) {
-                    if !is_blacklisted_url(&url) && !is_listing_page(&url, base) {
-                        items.push(it);
-                        return;
-                    }
-                }
-                items.push(it);
-                return;
-            }
+// Declared page language: <html lang>, then Content-Language meta, then og:locale ("fr_FR" -> "fr-FR").
+fn page_language(doc: &Html) -> Option<String> {
+    let html_lang = doc.root_element().value().attr("lang").or_else(|| doc.root_element().value().attr("xml:lang"));
+    let meta = |css: &str| Selector::parse(css).ok()
+        .and_then(|sel| doc.select(&sel).next().and_then(|m| m.value().attr("content").map(str::to_string)));
+    html_lang.map(str::to_string)
+        .or_else(|| meta(r#"meta[http-equiv="content-language" i]"#))
+        .or_else(|| meta(r#"meta[property="og:locale"]"#))
+        // Content-Language may list several; the first is the primary one
+        .and_then(|l| l.split(',').next().map(|l| l.trim().replace('_', "-")))
+        .filter(|l| !l.is_empty())
+}
+
+// Does a language tag satisfy the preferred one? Exact match, else same primary subtag
+// ("de" takes "de-AT", and "pt-BR" falls back to plain "pt").
+fn lang_matches(tag: &str, preferred: &str) -> bool {
+    let norm = |l: &str| l.trim().replace('_', "-").to_lowercase();
+    let (tag, preferred) = (norm(tag), norm(preferred));
+    let primary = |l: &str| l.split('-').next().unwrap_or_default().to_string();
+    tag == preferred || (primary(&tag) == primary(&preferred) && (!tag.contains('-') || !preferred.contains('-')))
+}
+
+// --prefer-lang: the page's hreflang alternate in the preferred language (relative hrefs resolved),
+// exact tags before primary-subtag matches; None when the page itself is already in it.
+fn hreflang_variant(doc: &Html, page_url: &Url, preferred: &str) -> Option<Url> {
+    if page_language(doc).is_some_and(|l| lang_matches(&l, preferred)) { return None; }
+    let sel = Selector::parse(r#"link[rel~="alternate"][hreflang][href]"#).ok()?;
+    let variants: Vec<(String, Url)> = doc.select(&sel)
+        .filter_map(|l| {
+            let lang = l.value().attr("hreflang")?.trim();
+            if lang.eq_ignore_ascii_case("x-default") { return None; }
+            let url = page_url.join(l.value().attr("href")?.trim()).ok()?;
+            Some((lang.to_string(), url))
+        })
+        .filter(|(_, u)| matches!(u.scheme(), "http" | "https"))
+        .collect();
+    variants.iter().find(|(l, _)| l.eq_ignore_ascii_case(preferred.trim()))
+        .or_else(|| variants.iter().find(|(l, _)| lang_matches(l, preferred)))
+        .map(|(_, u)| u.clone())
+        .filter(|u| canonicalize_url_str(u.as_str()) != canonicalize_url_str(page_url.as_str()))
+}
+
+// Publication date from the first <time> element with a usable value. The machine-readable
+// datetime attribute wins; text such as "March 15, 2024" is normalized to RFC 3339.
+fn time_element_date(doc: &Html) -> Option<String> {
+    let sel = Selector::parse("time").ok()?;
+    doc.select(&sel).find_map(|t| {
+        if let Some(dt) = t.value().attr("datetime").map(str::trim).filter(|d| parse_pub_date(d).is_some()) {
+            return Some(dt.to_string());
         }
+        let text = t.text().collect::<String>();
+        parse_pub_date(&text).map(|dt| dt.to_rfc3339())
+    })
+}
+
+fn extract_item_from_doc(doc: &Html, cand: &Url, base: &Url, items: &mut Vec<Item>) {
+    if is_consent_wall(doc) {
+        diag!("Skipping consent interstitial: {}", cand.as_str());
+        return;
+    }
+    if is_soft_404(doc, cand) {
+        diag!("Skipping soft-404 page: {}", cand.as_str());
+        return;
     }
 
-    // Meta/title fallback
+    let language = if opts().per_item_language { page_language(doc) } else { None };
+    let variant = opts().prefer_lang.as_deref().and_then(|l| hreflang_variant(doc, cand, l));
+    let word_count = if opts().reading_time { page_word_count(doc) } else { None };
+
+    // Meta tags: the fallback item, and the gap-filler for a JSON-LD one
     let mut found_title: Option<String> = None;
     let mut found_desc: Option<String> = None;
     let mut found_date: Option<String> = None;
+    let mut found_updated: Option<String> = None;
     let mut found_image: Option<String> = None;
+    let mut found_categories: Vec<String> = Vec::new();
+    let mut twitter_image: Option<String> = None;
+    let (mut found_player, mut found_video, mut found_video_type) = (None, None, None);
 
     if let Ok(sel_meta) = Selector::parse("meta") {
         for m in doc.select(&sel_meta) {
             if let Some(name) = m.value().attr("property").or_else(|| m.value().attr("name")) {
                 if let Some(content) = m.value().attr("content") {
                     match name.to_lowercase().as_str() {
-                        "og:title" | "twitter:title" | "title" => if found_title.is_none() { found_title = Some(fix_mojibake(&content.to_string())); },
-                        "og:description" | "twitter:description" | "description" => if found_desc.is_none() { found_desc = Some(fix_mojibake(&content.to_string())); },
-                        "og:image" | "twitter:image" | "image" => if found_image.is_none() { found_image = normalize_maybe_url(cand, content); },
-                        "article:published_time" | "pubdate" | "date" => if found_date.is_none() { found_date = Some(content.to_string()); },
+                        "og:title" | "twitter:title" | "title" if found_title.is_none() => found_title = Some(clean_title(content)),
+                        "og:description" | "twitter:description" | "description" if found_desc.is_none() => found_desc = Some(fix_mojibake(content)),
+                        "og:image" | "image" if found_image.is_none() => found_image = normalize_maybe_url(cand, content),
+                        "twitter:image" if found_image.is_none() => {
+                            found_image = normalize_maybe_url(cand, content);
+                            twitter_image = found_image.clone();
+                        }
+                        // the full-size card image, where twitter:image may be a thumbnail
+                        "twitter:image:src" if found_image.is_none() || found_image == twitter_image => {
+                            found_image = normalize_maybe_url(cand, content).or(found_image);
+                            twitter_image = None;
+                        }
+                        "twitter:player" if found_player.is_none() => found_player = normalize_maybe_url(cand, content),
+                        "twitter:player:stream" if found_video.is_none() => found_video = normalize_maybe_url(cand, content),
+                        "twitter:player:stream:content_type" if found_video_type.is_none() => found_video_type = Some(content.trim().to_string()),
+                        "article:published_time" | "pubdate" | "date" if found_date.is_none() => found_date = Some(content.to_string()),
+                        "article:modified_time" | "og:updated_time" if found_updated.is_none() => found_updated = Some(content.to_string()),
+                        "article:tag" => push_categories(&mut found_categories, [content.to_string()]),
+                        // legacy Google News list: "election, parliament, budget"
+                        "news_keywords" => push_categories(&mut found_categories, content.split(',').map(str::to_string)),
                         _ => (),
                     }
                 }
@@ -765,18 +3653,71 @@ fn extract_item_from_doc(doc: &Html, cand: &Url, base: &Url, items: &mut Vec<Ite
         }
     }
 
+    // Byline <time>: the datetime attribute when it parses, else the element's text
+    if found_date.is_none() {
+        found_date = time_element_date(doc);
+    }
+
+    if found_desc.is_none() && opts().first_paragraph_as_description {
+        found_desc = first_paragraph(doc);
+    }
+
+    if let Some(mut jitems) = extract_from_json_ld(doc, cand) {
+        // a live blog: every update is an item of its own
+        if jitems.len() > 1 && jitems.iter().all(|it| it.guid.is_some()) {
+            items.extend(jitems);
+            return;
+        }
+        if let Some(mut it) = jitems.pop() {
+            if it.link.is_empty() { it.link = cand.as_str().to_string(); }
+            if let Some(v) = &variant { it.link = v.to_string(); }
+            it.language = language.clone();
+            if it.word_count.is_none() { it.word_count = word_count; }
+            if it.player.is_none() { it.player = found_player.clone(); }
+            if it.video.is_none() {
+                it.video = found_video.clone();
+                it.video_type = found_video_type.clone();
+            }
+            if !opts().no_merge_metadata { push_categories(&mut it.categories, found_categories.clone()); }
+            if it.categories.is_empty() { it.categories = breadcrumb_categories(doc, cand, &it.link, &it.title); }
+            // JSON-LD is the base; og/meta tags fill what it lacks
+            if !opts().no_merge_metadata {
+                if it.image.is_none() {
+                    it.image = found_image.clone();
+                    it.images.extend(found_image.clone());
+                }
+                if it.description.is_none() { it.description = found_desc.clone(); }
+                if it.pub_date.is_none() { it.pub_date = found_date.clone(); }
+                if it.updated.is_none() { it.updated = found_updated.clone().or_else(|| it.pub_date.clone()); }
+            }
+            if let Some(img) = &it.image {
+                (it.image_caption, it.image_credit) = figure_caption(doc, cand, img);
+            }
+            if !is_error_page(doc, &it.title, &it.description) {
+                if let Ok(url) = Url::parse(&it.link) {
+                    if !is_blacklisted_url(&url) && !is_listing_page(&url, base) {
+                        items.push(it);
+                        return;
+                    }
+                }
+                items.push(it);
+                return;
+            }
+        }
+    }
+
     // Fallback to <h1,h2> or <title> if missing
     if found_title.is_none() {
         if let Ok(sel_h) = Selector::parse("h1,h2") {
             if let Some(hn) = doc.select(&sel_h).next() {
-                if let Some(t) = hn.text().next() { found_title = Some(fix_mojibake(&t.trim().to_string())); }
+                if let Some(t) = hn.text().next() { found_title = Some(clean_title(t.trim())); }
             }
         }
     }
     if found_title.is_none() {
         if let Ok(sel_title) = Selector::parse("title") {
             if let Some(tn) = doc.select(&sel_title).next() {
-                if let Some(t) = tn.text().next() { found_title = Some(fix_mojibake(&t.trim().to_string())); }
+                if let Some(t) = tn.text().next() { found_title = Some(clean_title(t.trim())); }
             }
         }
     }
@@ -784,30 +3725,124 @@ fn extract_item_from_doc(doc: &Html, cand: &Url, base: &Url, items: &mut Vec<Ite
     if found_image.is_none() {
         if let Ok(sel_img) = Selector::parse("img") {
             if let Some(imgn) = doc.select(&sel_img).next() {
-                if let Some(src) = imgn.value().attr("src") {
-                    found_image = normalize_maybe_url(cand, src);
+                found_image = best_image_source(imgn, cand, opts().compatible_images);
+            }
+            // lazy-loading pages often keep the real <img> only inside <noscript>
+            if found_image.is_none() {
+                for frag in noscript_fragments(doc) {
+                    if let Some(src) = frag.select(&sel_img).next().and_then(|n| best_image_source(n, cand, opts().compatible_images)) {
+                        found_image = Some(src);
+                        break;
+                    }
                 }
             }
         }
     }
 
+    // Gallery: primary image followed by any <figure> images (photo essays)
+    let mut found_images: Vec<String> = found_image.iter().cloned().collect();
+    if let Ok(sel_fig) = Selector::parse("figure img") {
+        for imgn in doc.select(&sel_fig) {
+            if found_images.len() >= opts().max_images { break; }
+            if let Some(u) = best_image_source(imgn, cand, opts().compatible_images) {
+                if !found_images.contains(&u) { found_images.push(u); }
+            }
+        }
+    }
+    if found_image.is_none() { found_image = found_images.first().cloned(); }
+    let (image_caption, image_credit) = found_image.as_deref()
+        .map(|img| figure_caption(doc, cand, img))
+        .unwrap_or_default();
+
+    let found_updated = found_updated.or_else(|| found_date.clone());
+
     if let Some(title) = found_title {
         if !is_error_page(doc, &title, &found_desc) {
-            let link_s = cand.as_str().to_string();
-            if let Ok(link_url) = Url::parse(&link_s) {
-                if !is_blacklisted_url(&link_url) && !is_listing_page(&link_url, base) {
-                    items.push(Item { title, link: link_s, description: found_desc, pub_date: found_date, image: found_image });
-                }
-            } else {
-                items.push(Item { title, link: link_s, description: found_desc, pub_date: found_date, image: found_image });
+            let link_s = variant.as_ref().unwrap_or(cand).as_str().to_string();
+            let categories = if found_categories.is_empty() { breadcrumb_categories(doc, cand, &link_s, &title) } else { found_categories };
+            let keep = Url::parse(&link_s).map_or(true, |u| !is_blacklisted_url(&u) && !is_listing_page(&u, base));
+            if keep {
+                items.push(Item {
+                    title, link: link_s, description: found_desc, pub_date: found_date, updated: found_updated,
+                    image: found_image, images: found_images, image_caption, image_credit, language, word_count, categories,
+                    player: found_player, video: found_video, video_type: found_video_type,
+                    ..Default::default()
+                });
             }
         }
     }
 }
 
+// Why an item's link can't be a feed entry link, if it can't: empty, not an absolute http(s)
+// URL (mailto:, tel:, javascript:), or an in-page anchor on the source page ("#", "#comments").
+// The bare source URL itself is allowed: it is the fallback for items without their own link.
+fn bad_link_reason(link: &str, base: &Url) -> Option<&'static str> {
+    let link = link.trim();
+    if link.is_empty() { return Some("empty link"); }
+    let Ok(mut u) = Url::parse(link) else { return Some("not an absolute URL") };
+    if u.scheme() != "http" && u.scheme() != "https" { return Some("non-http(s) link"); }
+    if u.fragment().is_some() {
+        u.set_fragment(None);
+        let mut page = base.clone();
+        page.set_fragment(None);
+        if u == page { return Some("anchor on the source page"); }
+    }
+    None
+}
+
+// Entries of a feed read with --repair-feed (or for a non-RSS format), through the same filters
+// as crawled items. `base` is the page the crawl started from.
+fn feed_source_items(text: &str, feed_url: &Url, base: &Url, filter: ItemFilter) -> Vec<Item> {
+    let mut items = parse_feed_lenient(text, feed_url);
+    filter_items_with(base, &mut items, filter);
+    items
+}
+
+// Articles in the start page's own JSON-LD, through the same filters as crawled items.
+fn start_page_json_ld_items(document: &Html, start_url: &Url, filter: ItemFilter) -> Option<Vec<Item>> {
+    let mut items = extract_from_json_ld(document, start_url)?;
+    items.retain(|it| !is_error_page(document, &it.title, &it.description));
+    filter_items_with(start_url, &mut items, filter);
+    Some(items)
+}
+
+// The flag-driven part of filter_items, split out so tests can set it without touching opts().
+#[derive(Copy, Clone, Debug)]
+struct ItemFilter {
+    dedup_by: DedupKey,
+    require_image: bool,
+    strict_encoding: bool,
+    max_items_per_host: Option<usize>,
+}
+
+impl ItemFilter {
+    fn from_opts() -> Self {
+        ItemFilter {
+            dedup_by: opts().dedup_by,
+            require_image: opts().require_image,
+            strict_encoding: opts().strict_encoding,
+            max_items_per_host: opts().max_items_per_host,
+        }
+    }
+}
+
 fn filter_items(base: &Url, items: &mut Vec<Item>) {
-    let mut seen_links = HashSet::new();
+    filter_items_with(base, items, ItemFilter::from_opts());
+}
+
+fn filter_items_with(base: &Url, items: &mut Vec<Item>, filter: ItemFilter) {
+    let before = items.len();
+    retain_feed_items(base, items, filter);
+    STATS.dropped.fetch_add(before - items.len(), Ordering::Relaxed);
+}
+
+// filter_items without the drop count, for passes over items that may be filtered again.
+fn retain_feed_items(base: &Url, items: &mut Vec<Item>, filter: ItemFilter) {
     items.retain(|it| {
+        if let Some(reason) = bad_link_reason(&it.link, base).filter(|_| it.guid.is_none() || it.link.is_empty()) {
+            if verbose() { diag!("Dropping item {:?} ({}): {}", it.title, reason, it.link); }
+            return false;
+        }
         let canon = canonicalize_url_str(&it.link);
 
         if let Ok(u) = Url::parse(&canon) {
@@ -820,20 +3855,179 @@ fn filter_items(base: &Url, items: &mut Vec<Item>) {
 
         let title_low = it.title.to_lowercase();
         let promo_words = ["subscribe", "subscription", "donate", "support", "newsletter", "become a member", "subscribe to", "subscribe now"];
-        if promo_words.iter().any(|pw| title_low.contains(pw)) { return false; }
+        !promo_words.iter().any(|pw| title_low.contains(pw))
+    });
+    dedup_items(base, items, filter.dedup_by);
+    // items reach here fully extracted, so a missing image means none was found at all
+    if filter.require_image { drop_imageless(items); }
+    if filter.strict_encoding { drop_garbled(items); }
+    if let Some(n) = filter.max_items_per_host { cap_per_host(items, n); }
+}
+
+// Exact dedup on the --dedup-by key, in place. Links and ids keep the first item; a title or
+// content match is a republication, so prefer_duplicate picks which to keep (the newest, when dated).
+fn dedup_items(base: &Url, items: &mut Vec<Item>, by: DedupKey) {
+    let mut index: HashMap<String, usize> = HashMap::new();
+    let mut kept: Vec<Item> = Vec::with_capacity(items.len());
+    for it in items.drain(..) {
+        match index.entry(dedup_key(&it, base, by)) {
+            Entry::Occupied(e) => {
+                let old = &mut kept[*e.get()];
+                if matches!(by, DedupKey::Title | DedupKey::ContentHash) && prefer_duplicate(&it, old) { *old = it; }
+            }
+            Entry::Vacant(e) => {
+                e.insert(kept.len());
+                kept.push(it);
+            }
+        }
+    }
+    *items = kept;
+}
+
+// Seen-set key for --dedup-by. Link keys on the GUID written to the feed, so items that all
+// fell back to the channel link aren't collapsed; only http(s) source ids (live-blog
+// anchors) are honored there, while guid trusts whatever id the source gave. Items with no
+// title or text to key on fall back to the link key rather than all collapsing into one.
+fn dedup_key(it: &Item, base: &Url, by: DedupKey) -> String {
+    let link_key = || match &it.guid {
+        Some(g) if !Url::parse(g).is_ok_and(|u| matches!(u.scheme(), "http" | "https")) => item_guid(&Item { guid: None, ..it.clone() }, base).0,
+        _ => item_guid(it, base).0,
+    };
+    match by {
+        DedupKey::Link => link_key(),
+        DedupKey::Guid => it.guid.clone().unwrap_or_else(link_key),
+        DedupKey::Title => {
+            let title = normalized_title(&it.title);
+            if title.is_empty() { link_key() } else { format!("title:{}", title) }
+        }
+        DedupKey::ContentHash => {
+            let title = normalized_title(&it.title);
+            let text = normalized_title(it.description.as_deref().unwrap_or(""));
+            if title.is_empty() && text.is_empty() { return link_key(); }
+            format!("content:{:016x}", fnv1a64(format!("{}\n{}", title, text).as_bytes()))
+        }
+    }
+}
+
+// --require-image
+fn drop_imageless(items: &mut Vec<Item>) {
+    items.retain(|it| {
+        if it.image.is_some() { return true; }
+        if verbose() { diag!("Dropping item {:?} (no image): {}", it.title, it.link); }
+        false
+    });
+}
+
+// UTF-8 lead bytes Â/Ã read as Windows-1252 and followed by a continuation byte, the "â€"
+// that starts curly quotes and dashes, "ï¿" from a replacement character, and runs of lead
+// bytes left by repeated misdecoding. "SÃO PAULO" and other real uses don't match.
+static RE_MOJIBAKE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"[ÂÃ][\u{A0}-\u{BF}€‚ƒ„…†‡ˆ‰Š‹ŒŽ‘’“”•–—˜™š›œžŸ]|â€|â‚¬|ï¿|[ÂÃâ]{2}").unwrap()
+});
+
+fn looks_garbled(s: &str) -> bool {
+    s.contains('\u{FFFD}') || RE_MOJIBAKE.is_match(s)
+}
+
+// --strict-encoding: text fix_mojibake could not repair
+fn drop_garbled(items: &mut Vec<Item>) {
+    items.retain(|it| {
+        if !looks_garbled(&it.title) && !it.description.as_deref().is_some_and(looks_garbled) { return true; }
+        diag!("Dropping item {:?} (unrepairable encoding): {}", it.title, it.link);
+        false
+    });
+}
 
-        if seen_links.contains(&canon) { return false; }
-        seen_links.insert(canon);
-        true
+// --max-items-per-host: sort newest first, then keep the first N items of each host so one
+// prolific site can't crowd out the rest of a merged feed.
+fn cap_per_host(items: &mut Vec<Item>, n: usize) {
+    sort_newest_first(items);
+    let mut per_host: HashMap<String, usize> = HashMap::new();
+    items.retain(|it| {
+        let host = Url::parse(&it.link).ok()
+            .and_then(|u| u.host_str().map(|h| h.trim_start_matches("www.").to_ascii_lowercase()))
+            .unwrap_or_default();
+        let count = per_host.entry(host).or_default();
+        *count += 1;
+        *count <= n
     });
 }
 
+// Word-bigram shingles of lowercased alphanumeric text, used for cheap near-duplicate checks.
+fn shingles(text: &str) -> HashSet<String> {
+    let words: Vec<String> = text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(|w| w.to_lowercase())
+        .collect();
+    if words.len() < 2 { return words.into_iter().collect(); }
+    words.windows(2).map(|w| w.join(" ")).collect()
+}
+
+// Jaccard similarity of two items: descriptions when both have one, otherwise titles.
+fn item_similarity(a: &Item, b: &Item) -> f64 {
+    let (sa, sb) = match (&a.description, &b.description) {
+        (Some(da), Some(db)) if !da.trim().is_empty() && !db.trim().is_empty() => (shingles(da), shingles(db)),
+        _ => (shingles(&a.title), shingles(&b.title)),
+    };
+    if sa.is_empty() || sb.is_empty() { return 0.0; }
+    let inter = sa.intersection(&sb).count() as f64;
+    let union = sa.union(&sb).count() as f64;
+    inter / union
+}
+
+// How much metadata an item carries; the richest of a duplicate group is kept.
+fn item_richness(it: &Item) -> usize {
+    [it.description.is_some(), it.pub_date.is_some(), it.image.is_some()].iter().filter(|b| **b).count()
+        + it.images.len().saturating_sub(1)
+        + it.description.as_ref().map(|d| d.len() / 200).unwrap_or(0)
+}
+
+// Title compared for republished articles: lowercase words, punctuation and spacing ignored.
+fn normalized_title(title: &str) -> String {
+    title.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(|w| w.to_lowercase())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// Which of two duplicates to keep: a re-dated republication (same title, different dates)
+// keeps the newest, anything else the richest.
+fn prefer_duplicate(new: &Item, old: &Item) -> bool {
+    if normalized_title(&new.title) == normalized_title(&old.title) {
+        let date = |it: &Item| it.pub_date.as_deref().and_then(parse_pub_date);
+        if let (Some(dn), Some(dold)) = (date(new), date(old)) {
+            if dn != dold { return dn > dold; }
+        }
+    }
+    item_richness(new) > item_richness(old)
+}
 
+// Drop near-duplicate items (similarity at `threshold` or identical titles), keeping the
+// preferred one of each group in place. Runs after the link-based dedup in filter_items.
+fn dedupe_similar(items: &mut Vec<Item>, threshold: f64) {
+    let mut kept: Vec<Item> = Vec::with_capacity(items.len());
+    for it in items.drain(..) {
+        let title = normalized_title(&it.title);
+        let dup = kept.iter().position(|k| {
+            (!title.is_empty() && normalized_title(&k.title) == title) || item_similarity(k, &it) >= threshold
+        });
+        match dup {
+            Some(idx) => {
+                if prefer_duplicate(&it, &kept[idx]) { kept[idx] = it; }
+            }
+            None => kept.push(it),
+        }
+    }
+    *items = kept;
+}
 
 // Heuristic: determine if a URL is a listing/section page rather than an article
 fn is_listing_page(u: &Url, base: &Url) -> bool {
     // same-origin required
     if u.domain() != base.domain() { return false; }
+    // forum/CMS article id in the query (--article-query-param)
+    if has_article_query_param(u) { return false; }
     let path = u.path();
     // root path is a listing
     if path == "/" || path.is_empty() { return true; }
@@ -856,30 +4050,85 @@ fn is_listing_page(u: &Url, base: &Url) -> bool {
     false
 }
 
-fn is_blacklisted_url(u: &Url) -> bool {
-    // blacklist obvious non-article keywords in path or query
-    if let Some(q) = u.query() {
-        let ql = q.to_lowercase();
-        if ql.contains("newsletter") || ql.contains("subscribe") || ql.contains("signup") { return true; }
+fn is_blacklisted_url(u: &Url) -> bool {
+    // blacklist obvious non-article keywords in path or query
+    if let Some(q) = u.query() {
+        let ql = q.to_lowercase();
+        if ql.contains("newsletter") || ql.contains("subscribe") || ql.contains("signup") { return true; }
+    }
+    let path = u.path().to_lowercase();
+    let bad = ["newsletter", "subscribe", "signup", "quizzes", "quiz", "jobs", "careers", "advert", "ads", "promo", "subscribe", "privacy", "terms", "/about", "login", "signin", "/stories/new", "/store", "/subscriptions", "/donate"]; 
+    for b in &bad {
+        if path.contains(b) { return true; }
+    }
+    false
+}
+
+// Whether the URL carries one of the --article-query-param names (case-insensitive).
+fn has_article_query_param(u: &Url) -> bool {
+    let names = &opts().article_query_param;
+    !names.is_empty() && u.query_pairs().any(|(k, _)| is_article_query_param(&k))
+}
+
+fn is_article_query_param(key: &str) -> bool {
+    opts().article_query_param.iter().any(|n| n.eq_ignore_ascii_case(key))
+}
+
+// --normalize-links auto: the policy detected on the start page
+static LINK_SLASH: OnceCell<SlashPolicy> = OnceCell::new();
+
+fn slash_policy() -> SlashPolicy {
+    match OPTIONS.get().map_or(SlashPolicy::Leave, |a| a.normalize_links) {
+        SlashPolicy::Auto => LINK_SLASH.get().copied().unwrap_or(SlashPolicy::Leave),
+        policy => policy,
+    }
+}
+
+// Directory-like paths only: never the root, and never a last segment with a file extension.
+fn apply_slash_policy(u: &mut Url, policy: SlashPolicy) {
+    let path = u.path();
+    let last = path.trim_end_matches('/').rsplit('/').next().unwrap_or_default();
+    if path == "/" || last.contains('.') { return; }
+    let new_path = match policy {
+        SlashPolicy::Add if !path.ends_with('/') => format!("{}/", path),
+        SlashPolicy::Remove if path.ends_with('/') => path.trim_end_matches('/').to_string(),
+        _ => return,
+    };
+    u.set_path(&new_path);
+}
+
+// Majority trailing-slash style of the start page's same-host links (Leave when it has none).
+fn slash_convention(document: &Html, base: &Url) -> SlashPolicy {
+    let Ok(sel) = Selector::parse("a[href]") else { return SlashPolicy::Leave };
+    let (mut with, mut without) = (0, 0);
+    for href in document.select(&sel).filter_map(|a| a.value().attr("href")) {
+        let Ok(u) = base.join(&clean_href(href)) else { continue };
+        if u.host_str() != base.host_str() || u.path() == "/" { continue; }
+        if u.path().rsplit('/').find(|seg| !seg.is_empty()).is_some_and(|seg| seg.contains('.')) { continue; }
+        if u.path().ends_with('/') { with += 1 } else { without += 1 }
     }
-    let path = u.path().to_lowercase();
-    let bad = ["newsletter", "subscribe", "signup", "quizzes", "quiz", "jobs", "careers", "advert", "ads", "promo", "subscribe", "privacy", "terms", "/about", "login", "signin", "/stories/new", "/store", "/subscriptions", "/donate"]; 
-    for b in &bad {
-        if path.contains(b) { return true; }
+    match (with, without) {
+        (0, 0) => SlashPolicy::Leave,
+        (w, wo) if w > wo => SlashPolicy::Add,
+        _ => SlashPolicy::Remove,
     }
-    false
 }
 
 // Produce a canonical form for URL string comparisons: remove fragment and common tracking query params
 fn canonicalize_url_str(s: &str) -> String {
+    canonicalize_url_with(s, slash_policy())
+}
+
+fn canonicalize_url_with(s: &str, slash: SlashPolicy) -> String {
     if let Ok(mut u) = Url::parse(s) {
         // remove fragment
         u.set_fragment(None);
+        apply_slash_policy(&mut u, slash);
         // filter query params
         if let Some(q) = u.query() {
             let pairs = form_urlencoded::parse(q.as_bytes()).into_owned().filter(|(k, _)| {
                 let kl = k.to_lowercase();
-                !(kl.starts_with("utm_") || kl == "fbclid" || kl == "gclid")
+                is_article_query_param(&kl) || !(kl.starts_with("utm_") || kl == "fbclid" || kl == "gclid")
             }).collect::<Vec<(String, String)>>();
             // rebuild query
             if pairs.is_empty() {
@@ -896,17 +4145,38 @@ fn canonicalize_url_str(s: &str) -> String {
     s.to_string()
 }
 
-use quick_xml::events::{BytesStart, BytesEnd, BytesText};
+use quick_xml::events::{BytesStart, BytesEnd, BytesText, BytesCData};
 
 fn write_text_element<W: Write>(w: &mut Writer<W>, name: &str, text: &str) -> Result<(), Box<dyn Error>> {
     w.write_event(Event::Start(BytesStart::new(name)))?;
-    // sanitize text: decode HTML entities once, remove control characters that are invalid in XML
+    w.write_event(Event::Text(BytesText::new(&element_text(text))))?;
+    w.write_event(Event::End(BytesEnd::new(name)))?;
+    Ok(())
+}
+
+// sanitize text: decode HTML entities once, remove control characters that are invalid in XML
+fn element_text(text: &str) -> String {
     let mut s = sanitize_text(text);
     if s.len() > MAX_TEXT_LEN {
-        s.truncate(MAX_TEXT_LEN);
+        let mut cut = MAX_TEXT_LEN;
+        while !s.is_char_boundary(cut) { cut -= 1; }
+        s.truncate(cut);
         s.push_str("… (truncated)");
     }
-    w.write_event(Event::Text(BytesText::new(&s)))?;
+    s
+}
+
+// Like write_text_element, but as CDATA (--cdata-descriptions). A "]]>" in the text would end
+// the section early, so the text is split there across two sections: "a]]" + ">b".
+fn write_cdata_element<W: Write>(w: &mut Writer<W>, name: &str, text: &str) -> Result<(), Box<dyn Error>> {
+    w.write_event(Event::Start(BytesStart::new(name)))?;
+    let s = element_text(text);
+    let mut rest = s.as_str();
+    while let Some(pos) = rest.find("]]>") {
+        w.write_event(Event::CData(BytesCData::new(&rest[..pos + 2])))?;
+        rest = &rest[pos + 2..];
+    }
+    w.write_event(Event::CData(BytesCData::new(rest)))?;
     w.write_event(Event::End(BytesEnd::new(name)))?;
     Ok(())
 }
@@ -921,63 +4191,992 @@ fn sanitize_text(input: &str) -> String {
         .filter(|&c| {
             let code = c as u32;
             if code == 0x09 || code == 0x0A || code == 0x0D { return true; }
-            // allow printable characters and other unicode categories (>= 0x20)
-            code >= 0x20
+            // allow printable characters and other unicode categories (>= 0x20), except the
+            // two noncharacters XML forbids
+            code >= 0x20 && code != 0xFFFE && code != 0xFFFF
         })
         .collect::<String>()
 }
 
-// Try to produce RFC-2822 (RFC822 compatible) pubDate values. Fall back to original raw string.
-fn format_pub_date(raw: &str) -> String {
-    // Try RFC3339 (ISO 8601) first, then RFC2822, otherwise return raw
-    if let Ok(dt) = DateTime::parse_from_rfc3339(raw) {
-        return dt.to_rfc2822();
+// Stable sort by parsed pub_date, newest first; undated items keep their order after dated ones.
+fn sort_newest_first(items: &mut [Item]) {
+    items.sort_by_key(|it| std::cmp::Reverse(it.pub_date.as_deref().and_then(parse_pub_date)));
+}
+
+// Zone-less layouts seen in bylines and <time> text; read as UTC.
+const NAIVE_DATETIME_FORMATS: &[&str] = &["%Y-%m-%dT%H:%M:%S", "%Y-%m-%dT%H:%M", "%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M"];
+const NAIVE_DATE_FORMATS: &[&str] = &["%Y-%m-%d", "%B %d, %Y", "%b %d, %Y", "%d %B %Y", "%d %b %Y", "%Y/%m/%d"];
+
+// Parse a raw date string: RFC3339 (ISO 8601) first, then RFC2822, then common zone-less
+// date/time layouts ("2024-03-15 10:00", "March 15, 2024"), taken as UTC.
+fn parse_pub_date(raw: &str) -> Option<DateTime<FixedOffset>> {
+    let raw = raw.trim();
+    if let Ok(dt) = DateTime::parse_from_rfc3339(raw).or_else(|_| DateTime::parse_from_rfc2822(raw)) {
+        return Some(dt);
+    }
+    let utc = FixedOffset::east_opt(0)?;
+    let naive = NAIVE_DATETIME_FORMATS.iter()
+        .find_map(|f| chrono::NaiveDateTime::parse_from_str(raw, f).ok())
+        .or_else(|| {
+            let cleaned = raw.replace(['\u{a0}', '.'], " ");
+            let cleaned = RE_WHITESPACE.replace_all(cleaned.trim(), " ");
+            NAIVE_DATE_FORMATS.iter()
+                .find_map(|f| chrono::NaiveDate::parse_from_str(&cleaned, f).ok())
+                .and_then(|d| d.and_hms_opt(0, 0, 0))
+        })?;
+    Some(naive.and_utc().with_timezone(&utc))
+}
+
+
+// Channel-level metadata found on the start page; missing fields fall back to generic values.
+#[derive(Debug, Default, PartialEq)]
+struct Channel {
+    // publication/organization name, used as the channel title
+    publisher: Option<String>,
+    // copyright line (JSON-LD copyrightHolder, else the page footer)
+    copyright: Option<String>,
+    // show artwork of a podcast series (JSON-LD PodcastSeries / partOfSeries image)
+    image: Option<String>,
+    // --auto-contact: "email (Name)" of the JSON-LD publisher or author
+    managing_editor: Option<String>,
+    // site logo for the channel <image>, per --feed-image-from
+    logo: Option<String>,
+}
+
+impl Channel {
+    fn title(&self, base: &Url) -> String {
+        match &self.publisher {
+            Some(name) => name.clone(),
+            None => format!("Feed for {}", base.host_str().unwrap_or(base.as_str())),
+        }
+    }
+}
+
+static RE_COPYRIGHT: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)(?:©|\(c\)|copyright)\s*(?:©\s*)?\d{4}(?:\s*[-–]\s*\d{4})?[^.|•\n]{0,80}").unwrap());
+
+// All JSON objects in the page's JSON-LD, flattened through arrays and @graph.
+fn json_ld_nodes(document: &Html, base: &Url) -> Vec<JsonValue> {
+    fn walk(v: JsonValue, out: &mut Vec<JsonValue>) {
+        match v {
+            JsonValue::Array(arr) => arr.into_iter().take(MAX_JSON_LD_NODES).for_each(|x| walk(x, out)),
+            JsonValue::Object(mut obj) => {
+                if let Some(graph) = obj.remove("@graph") { walk(graph, out); }
+                out.push(JsonValue::Object(obj));
+            }
+            _ => {}
+        }
+    }
+    let mut out = Vec::new();
+    let Ok(sel) = Selector::parse(r#"script[type="application/ld+json"]"#) else { return out };
+    for node in document.select(&sel) {
+        if let Some(json) = parse_json_ld_block(&node.text().collect::<String>(), base) { walk(json, &mut out); }
+    }
+    out
+}
+
+// Add trimmed, non-empty category names not already present (case-insensitively).
+fn push_categories(categories: &mut Vec<String>, names: impl IntoIterator<Item = String>) {
+    for name in names {
+        let name = fix_mojibake(name.trim());
+        if name.is_empty() || categories.iter().any(|c| c.eq_ignore_ascii_case(&name)) { continue; }
+        categories.push(name);
+    }
+}
+
+// Section names from a JSON-LD BreadcrumbList, outermost first, without the home crumb and
+// without the leaf when it is the article itself (same URL or same name as the title).
+fn breadcrumb_categories(document: &Html, base: &Url, link: &str, title: &str) -> Vec<String> {
+    let nodes = json_ld_nodes(document, base);
+    let Some(list) = nodes.iter().find(|n| n.get("@type").and_then(|t| t.as_str()) == Some("BreadcrumbList")) else {
+        return Vec::new();
+    };
+    let mut crumbs: Vec<(i64, String, Option<Url>)> = list.get("itemListElement")
+        .and_then(|e| e.as_array())
+        .map(|arr| arr.iter().enumerate().filter_map(|(i, el)| {
+            let item = el.get("item");
+            let name = el.get("name").and_then(|n| n.as_str())
+                .or_else(|| item.and_then(|it| it.get("name")).and_then(|n| n.as_str()))?;
+            let url = item.and_then(|it| it.as_str().or_else(|| it.get("@id").and_then(|u| u.as_str())))
+                .and_then(|u| base.join(u.trim()).ok());
+            let position = el.get("position").and_then(|p| p.as_i64().or_else(|| p.as_str()?.parse().ok())).unwrap_or(i as i64);
+            Some((position, name.trim().to_string(), url))
+        }).collect())
+        .unwrap_or_default();
+    crumbs.sort_by_key(|(pos, _, _)| *pos);
+
+    let link = canonicalize_url_str(link);
+    let names = crumbs.into_iter().filter(|(_, name, url)| {
+        let home = matches!(name.to_lowercase().as_str(), "home" | "homepage" | "start" | "front page")
+            || url.as_ref().is_some_and(|u| u.path() == "/");
+        let leaf = name.eq_ignore_ascii_case(title.trim())
+            || url.as_ref().is_some_and(|u| canonicalize_url_str(u.as_str()) == link);
+        !home && !leaf
+    }).map(|(_, name, _)| name);
+    let mut categories = Vec::new();
+    push_categories(&mut categories, names);
+    categories
+}
+
+// A schema.org name given either as a string or as an object with "name".
+fn json_ld_name(v: &JsonValue) -> Option<String> {
+    v.as_str().or_else(|| v.get("name").and_then(|n| n.as_str()))
+        .map(fix_mojibake)
+        .filter(|s| !s.is_empty())
+}
+
+// Publisher name: the page's JSON-LD publisher or Organization/WebSite, else the most common item
+// publisher, else og:site_name. Copyright: JSON-LD copyrightHolder/Year, else a footer "© ..." line.
+fn channel_metadata(document: &Html, base: &Url, items: &[Item]) -> Channel {
+    let nodes = json_ld_nodes(document, base);
+    let is_org = |n: &JsonValue| n.get("@type").and_then(|t| t.as_str())
+        .is_some_and(|t| t.ends_with("Organization") || t == "WebSite");
+
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for p in items.iter().filter_map(|it| it.publisher.as_deref()) { *counts.entry(p).or_insert(0) += 1; }
+    let common = counts.into_iter().max_by(|a, b| a.1.cmp(&b.1).then(b.0.cmp(a.0))).map(|(p, _)| p.to_string());
+    let site_name = Selector::parse(r#"meta[property="og:site_name"]"#).ok()
+        .and_then(|sel| document.select(&sel).next().and_then(|m| m.value().attr("content")).map(fix_mojibake))
+        .filter(|s| !s.is_empty());
+    let publisher = nodes.iter().find_map(|n| n.get("publisher").and_then(json_ld_name))
+        .or_else(|| nodes.iter().filter(|n| is_org(n)).find_map(|n| n.get("name").and_then(json_ld_name)))
+        .or(common)
+        .or(site_name);
+
+    let copyright = nodes.iter().find_map(|n| {
+        let holder = n.get("copyrightHolder").and_then(json_ld_name)?;
+        let year = n.get("copyrightYear").and_then(|y| y.as_u64().map(|y| y.to_string()).or_else(|| y.as_str().map(str::to_string)));
+        Some(match year {
+            Some(y) => format!("© {} {}", y, holder),
+            None => format!("© {}", holder),
+        })
+    }).or_else(|| {
+        let sel = Selector::parse("footer, #footer, .footer, .site-footer").ok()?;
+        document.select(&sel).find_map(|f| {
+            let text = fix_mojibake(&f.text().collect::<Vec<_>>().join(" "));
+            RE_COPYRIGHT.find(&text).map(|m| m.as_str().trim().to_string())
+        })
+    });
+
+    let is_series = |n: &JsonValue| n.get("@type").and_then(|t| t.as_str()) == Some("PodcastSeries");
+    let image = nodes.iter()
+        .filter_map(|n| if is_series(n) { Some(n) } else { n.get("partOfSeries") })
+        .find_map(|series| series.get("image").and_then(|img| json_ld_images(img, base).into_iter().next()));
+
+    let managing_editor = if opts().auto_contact { json_ld_contact(&nodes) } else { None };
+    let logo = channel_logo(document, &nodes, base, &opts().feed_image_from);
+
+    Channel { publisher, copyright, image, managing_editor, logo }
+}
+
+// The first site logo found among the sources, tried in the given order.
+fn channel_logo(document: &Html, nodes: &[JsonValue], base: &Url, order: &[ImageSource]) -> Option<String> {
+    let is_org = |n: &JsonValue| n.get("@type").and_then(|t| t.as_str()).is_some_and(|t| t.ends_with("Organization"));
+    let attr = |css: &str, name: &str| Selector::parse(css).ok().and_then(|sel| {
+        document.select(&sel).find_map(|el| normalize_maybe_url(base, el.value().attr(name)?.trim()))
+    });
+    order.iter().find_map(|source| match source {
+        ImageSource::Jsonld => nodes.iter()
+            .filter_map(|n| n.get("publisher").or_else(|| is_org(n).then_some(n)))
+            .find_map(|org| org.get("logo").and_then(|l| json_ld_images(l, base).into_iter().next())),
+        ImageSource::Og => attr(r#"meta[property="og:image"]"#, "content"),
+        ImageSource::Icon => attr(r#"link[rel~="apple-touch-icon"][href]"#, "href")
+            .or_else(|| attr(r#"link[rel~="icon"][href]"#, "href")),
+    })
+}
+
+// "email (Name)" of the first JSON-LD publisher or author that lists a valid email.
+fn json_ld_contact(nodes: &[JsonValue]) -> Option<String> {
+    nodes.iter()
+        .flat_map(|n| ["publisher", "author"].into_iter().filter_map(|k| n.get(k)))
+        .flat_map(|v| v.as_array().cloned().unwrap_or_else(|| vec![v.clone()]))
+        .find_map(|who| {
+            let email = who.get("email")?.as_str()?.trim().trim_start_matches("mailto:");
+            let contact = match json_ld_name(&who) {
+                Some(name) => format!("{} ({})", email, name),
+                None => email.to_string(),
+            };
+            contact_email(&contact).ok()
+        })
+}
+
+// Per-item quality problems for --validation-comments, "item N (title): issue; issue".
+fn feed_warnings(base: &Url, items: &[Item]) -> Vec<String> {
+    items.iter().enumerate().filter_map(|(i, it)| {
+        let mut issues = Vec::new();
+        match it.pub_date.as_deref() {
+            None => issues.push("no date".to_string()),
+            Some(d) if parse_pub_date(d).is_none() => issues.push(format!("unparseable date {:?}", d)),
+            Some(_) => (),
+        }
+        if it.description.as_deref().is_none_or(|d| d.trim().is_empty()) { issues.push("empty description".to_string()); }
+        if let Some(reason) = bad_link_reason(&it.link, base) {
+            issues.push(reason.to_string());
+        } else if it.guid.is_none() && canonicalize_url_str(&it.link) == canonicalize_url_str(base.as_str()) {
+            issues.push("links to the channel page".to_string());
+        } else if Url::parse(&it.link).is_ok_and(|u| u.query_pairs().any(|(k, _)| k.starts_with("utm_"))) {
+            issues.push("tracking parameters in link".to_string());
+        }
+        if issues.is_empty() { return None; }
+        Some(format!("item {} ({}): {}", i + 1, truncate_chars(&it.title, 60), issues.join("; ")))
+    }).collect()
+}
+
+// Comment body that can't end the comment early: XML forbids "--" inside and a trailing "-".
+fn xml_comment_text(s: &str) -> String {
+    let mut out = sanitize_text(s).replace("--", "- -");
+    while out.contains("--") { out = out.replace("--", "- -"); }
+    if out.ends_with('-') { out.push(' '); }
+    out
+}
+
+fn write_validation_comments<W: Write>(w: &mut Writer<W>, base: &Url, items: &[Item]) -> Result<(), Box<dyn Error>> {
+    let warnings = feed_warnings(base, items);
+    if warnings.is_empty() { return Ok(()); }
+    let header = format!(" html2rss validation: {} of {} item(s) with warnings ", warnings.len(), items.len());
+    w.write_event(Event::Comment(BytesText::from_escaped(xml_comment_text(&header))))?;
+    for warning in &warnings {
+        w.write_event(Event::Comment(BytesText::from_escaped(xml_comment_text(&format!(" {} ", warning)))))?;
+    }
+    Ok(())
+}
+
+// Serialize the final item list in the selected --format.
+fn write_output(base: &Url, channel: &Channel, items: &Vec<Item>) -> Result<(), Box<dyn Error>> {
+    if opts().dry_crawl {
+        report_crawl_stats(items);
+        return Ok(());
+    }
+    if let Some(path) = &opts().html_index {
+        fs::write(path, html_index(base, channel, items))
+            .map_err(|e| format!("writing HTML index {}: {}", path.display(), e))?;
+    }
+    if opts().preview {
+        let index = opts().preview_index as usize;
+        match items.get(index - 1) {
+            Some(it) => eprint!("{}", preview_item(it, index, items.len())),
+            None => eprintln!("Preview: no item #{} ({} extracted)", index, items.len()),
+        }
+    }
+    let templated;
+    let items = if opts().title_template.is_some() || opts().description_template.is_some() {
+        templated = items.iter().map(|it| apply_templates(it, base, channel)).collect::<Vec<_>>();
+        &templated
+    } else {
+        items
+    };
+    if let Some(path) = &opts().merge_into {
+        return merge_into_file(path, base, channel, items, opts().merge_keep)
+            .map_err(|e| format!("merging into {}: {}", path.display(), e).into());
+    }
+    match opts().format {
+        OutputFormat::Rss => write_rss(base, channel, items),
+        OutputFormat::Rss10 => write_rss10(base, channel, items),
+        OutputFormat::Table => write_table(items),
+        OutputFormat::Ndjson => stream_new_items(base, items),
+    }
+}
+
+static RE_XML_ENCODING: Lazy<Regex> = Lazy::new(|| Regex::new(r#"^\s*<\?xml[^>]*encoding\s*=\s*["']([^"']+)["']"#).unwrap());
+
+// An XML file as text: BOM first, then the declaration's encoding, else UTF-8.
+fn decode_xml_bytes(bytes: &[u8]) -> String {
+    let head = String::from_utf8_lossy(&bytes[..bytes.len().min(256)]);
+    let declared = RE_XML_ENCODING.captures(&head)
+        .and_then(|c| Encoding::for_label(c[1].trim().as_bytes()))
+        .unwrap_or(UTF_8);
+    declared.decode(bytes).0.into_owned()
+}
+
+// --merge-into: read the existing feed (none yet is fine), merge and write it back via a
+// temporary file so an interrupted write can't truncate it.
+fn merge_into_file(path: &Path, base: &Url, channel: &Channel, items: &[Item], keep: usize) -> Result<(), Box<dyn Error>> {
+    let existing = match fs::read(path) {
+        Ok(bytes) => parse_feed_lenient(&decode_xml_bytes(&bytes), base),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Vec::new(),
+        Err(e) => return Err(e.into()),
+    };
+    let before = existing.len();
+    let merged = merge_items(existing, items.to_vec(), base, keep);
+    if verbose() { diag!("Merged {} new item(s) into {} existing; keeping {}", items.len(), before, merged.len()); }
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, transcode_xml(rss_document(base, channel, &merged)?, output_encoding()))?;
+    fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+// Existing items first and intact (missing fields filled from a fresh copy of the same item),
+// then unseen new ones; the newest `keep` survive.
+fn merge_items(existing: Vec<Item>, new: Vec<Item>, base: &Url, keep: usize) -> Vec<Item> {
+    let mut merged = existing;
+    let mut index: HashMap<String, usize> = merged.iter().enumerate().map(|(i, it)| (item_guid(it, base).0, i)).collect();
+    for it in new {
+        let key = item_guid(&it, base).0;
+        match index.get(&key) {
+            Some(&i) => {
+                let old = &mut merged[i];
+                if old.description.is_none() { old.description = it.description; }
+                if old.pub_date.is_none() { old.pub_date = it.pub_date; }
+                if old.image.is_none() {
+                    old.image = it.image;
+                    old.images = it.images;
+                }
+                if old.categories.is_empty() { old.categories = it.categories; }
+                if old.author.is_none() { old.author = it.author; }
+            }
+            None => {
+                index.insert(key, merged.len());
+                merged.push(it);
+            }
+        }
+    }
+    sort_newest_first(&mut merged);
+    merged.truncate(keep);
+    merged
+}
+
+// --title-template / --description-template for one item. Values are plain text; the
+// writers escape them like any other title or description.
+fn apply_templates(it: &Item, base: &Url, channel: &Channel) -> Item {
+    let host = Url::parse(&it.link).ok()
+        .and_then(|u| u.host_str().map(|h| h.trim_start_matches("www.").to_string()))
+        .unwrap_or_else(|| base.host_str().unwrap_or_default().trim_start_matches("www.").to_string());
+    let lookup = |name: &str| Some(match name {
+        "title" => it.title.clone(),
+        "description" => it.description.clone().unwrap_or_default(),
+        "site" => it.publisher.clone().unwrap_or_else(|| channel.title(base)),
+        "date" => it.pub_date.as_deref().and_then(parse_pub_date)
+            .map(|d| d.format("%Y-%m-%d").to_string()).unwrap_or_default(),
+        "host" => host.clone(),
+        _ => return None,
+    });
+    let mut out = it.clone();
+    // templates were validated at startup, so expansion can't fail here
+    if let Some(Ok(title)) = opts().title_template.as_deref().map(|t| expand_template(t, lookup)) {
+        out.title = title.trim().to_string();
+    }
+    if let Some(Ok(desc)) = opts().description_template.as_deref().map(|t| expand_template(t, lookup)) {
+        out.description = Some(desc.trim().to_string()).filter(|d| !d.is_empty());
+    }
+    out
+}
+
+// --preview: every field of one item, labeled and untruncated; "-" marks a missing value.
+fn preview_item(it: &Item, index: usize, total: usize) -> String {
+    fn opt<T: std::fmt::Display>(v: &Option<T>) -> String {
+        v.as_ref().map_or_else(|| "-".to_string(), T::to_string)
+    }
+    fn list(v: &[String]) -> String {
+        if v.is_empty() { "-".to_string() } else { v.join("\n                  ") }
+    }
+    let fields = [
+        ("title", it.title.clone()),
+        ("link", it.link.clone()),
+        ("original link", opt(&it.original_link)),
+        ("guid", opt(&it.guid)),
+        ("description", opt(&it.description)),
+        ("published", opt(&it.pub_date)),
+        ("updated", opt(&it.updated)),
+        ("image", opt(&it.image)),
+        ("image bytes", opt(&it.image_length)),
+        ("images", list(&it.images)),
+        ("image caption", opt(&it.image_caption)),
+        ("image credit", opt(&it.image_credit)),
+        ("author", opt(&it.author)),
+        ("author image", opt(&it.author_image)),
+        ("publisher", opt(&it.publisher)),
+        ("publisher logo", opt(&it.publisher_logo)),
+        ("categories", list(&it.categories)),
+        ("language", opt(&it.language)),
+        ("word count", opt(&it.word_count)),
+        ("audio", opt(&it.audio)),
+        ("audio type", opt(&it.audio_type)),
+        ("audio bytes", opt(&it.audio_length)),
+        ("duration", opt(&it.duration)),
+        ("episode", opt(&it.episode)),
+        ("player", opt(&it.player)),
+        ("video", opt(&it.video)),
+        ("video type", opt(&it.video_type)),
+        ("video bytes", opt(&it.video_length)),
+    ];
+    let mut out = format!("Preview of item {} of {}\n", index, total);
+    for (label, value) in fields {
+        out.push_str(&format!("  {:<16}{}\n", format!("{}:", label), value));
+    }
+    out
+}
+
+// --dry-crawl summary in place of the feed.
+fn report_crawl_stats(items: &[Item]) {
+    let elapsed = STATS.started.get().map(|t| t.elapsed()).unwrap_or_default();
+    let pages = STATS.pages.load(Ordering::Relaxed);
+    let dropped = STATS.dropped.load(Ordering::Relaxed);
+    eprintln!("Dry crawl finished in {:.2}s", elapsed.as_secs_f64());
+    eprintln!("  pages fetched:   {} ({} failed)", pages, STATS.failures.load(Ordering::Relaxed));
+    eprintln!("  bytes received:  {} KB", STATS.bytes.load(Ordering::Relaxed) / 1024);
+    if pages > 0 {
+        eprintln!("  avg per page:    {:.0} ms", elapsed.as_millis() as f64 / pages as f64);
+    }
+    eprintln!("  items found:     {}", items.len() + dropped);
+    eprintln!("  items filtered:  {}", dropped);
+    eprintln!("  items kept:      {}", items.len());
+}
+
+// --html-index: a self-contained "latest links" page. Every interpolated value is escaped.
+fn html_index(base: &Url, channel: &Channel, items: &[Item]) -> String {
+    use html_escape::{encode_double_quoted_attribute as attr, encode_text as text};
+    let title = channel.title(base);
+    let mut out = String::new();
+    out.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+    out.push_str("<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n");
+    out.push_str(&format!("<title>{}</title>\n", text(&title)));
+    out.push_str("<style>body{font:16px/1.5 system-ui,sans-serif;max-width:46em;margin:2em auto;padding:0 1em;color:#222}\
+li{margin:0 0 1.2em}time{color:#666;font-size:.85em;margin-left:.5em}p{margin:.2em 0 0}</style>\n");
+    out.push_str("</head>\n<body>\n");
+    out.push_str(&format!("<h1><a href=\"{}\">{}</a></h1>\n<ul>\n", attr(base.as_str()), text(&title)));
+    for it in items {
+        out.push_str(&format!("<li><a href=\"{}\">{}</a>", attr(&it.link), text(&it.title)));
+        if let Some(dt) = it.pub_date.as_deref().and_then(parse_pub_date) {
+            out.push_str(&format!("<time datetime=\"{}\">{}</time>", attr(&dt.to_rfc3339()), dt.format("%Y-%m-%d %H:%M")));
+        }
+        if let Some(desc) = &it.description {
+            out.push_str(&format!("<p>{}</p>", text(&sanitize_text(desc))));
+        }
+        out.push_str("</li>\n");
+    }
+    out.push_str("</ul>\n</body>\n</html>\n");
+    out
+}
+
+// GUIDs of items already written by --format ndjson, so each item goes out exactly once.
+static STREAMED: Lazy<Mutex<HashSet<String>>> = Lazy::new(|| Mutex::new(HashSet::new()));
+
+// One item as a JSON object; absent fields are null so every line has the same keys. Keys keep
+// this order (serde_json's preserve_order): title, link, guid, description, pub_date, updated,
+// image, images, original_link, language, author, publisher. Append new keys at the end.
+fn item_json(it: &Item, guid: &str) -> JsonValue {
+    serde_json::json!({
+        "title": it.title,
+        "link": it.link,
+        "guid": guid,
+        "description": it.description,
+        "pub_date": it.pub_date,
+        "updated": it.updated,
+        "image": it.image,
+        "images": it.images,
+        "original_link": it.original_link,
+        "language": it.language,
+        "author": it.author,
+        "publisher": it.publisher,
+    })
+}
+
+// Start-page channel for templating items streamed mid-crawl (--format ndjson), set by run.
+static STREAM_CHANNEL: OnceCell<Channel> = OnceCell::new();
+
+// --format ndjson mid-crawl: stream the items found so far, shaped as the end-of-run writers
+// would emit them. Errors are reported and the crawl goes on.
+fn stream_found_items(base: &Url, items: &[Item]) {
+    if opts().format != OutputFormat::Ndjson || opts().dry_crawl { return; }
+    let Some(channel) = STREAM_CHANNEL.get() else { return };
+    let ready = stream_ready_items(base, channel, items, ItemFilter::from_opts());
+    if let Err(e) = stream_new_items(base, &ready) { diag!("Failed to stream items: {}", e); }
+}
+
+// The crawl's items through the same filters, text passes and templates as the final list.
+// An item once written can't be taken back, so when a pass may prefer a later item over an
+// earlier one (--dedupe-similar, --max-items-per-host) nothing goes out until the end.
+fn stream_ready_items(base: &Url, channel: &Channel, items: &[Item], filter: ItemFilter) -> Vec<Item> {
+    if opts().dedupe_similar || filter.max_items_per_host.is_some() { return Vec::new(); }
+    let mut ready = items.to_vec();
+    retain_feed_items(base, &mut ready, filter);
+    shape_items(&mut ready);
+    if opts().fallback_date == Some(FallbackDate::Now) { stamp_undated(&mut ready, chrono::Utc::now()); }
+    if opts().title_template.is_some() || opts().description_template.is_some() {
+        ready = ready.iter().map(|it| apply_templates(it, base, channel)).collect();
+    }
+    ready
+}
+
+// --format ndjson: write (and flush) any finished items not yet written: from the crawl loop
+// through stream_found_items, and once more at the end with the final list.
+fn stream_new_items(base: &Url, items: &[Item]) -> Result<(), Box<dyn Error>> {
+    if opts().format != OutputFormat::Ndjson || opts().dry_crawl { return Ok(()); }
+    let mut streamed = STREAMED.lock().unwrap();
+    write_json_items(&mut stdout_writer(), base, items, &mut streamed, opts().json_pretty)
+}
+
+// One JSON document per item not in `written`, each flushed as soon as it is complete so a
+// reader (or interleaved stderr) never sees half an item.
+fn write_json_items<W: Write>(out: &mut W, base: &Url, items: &[Item], written: &mut HashSet<String>, pretty: bool) -> Result<(), Box<dyn Error>> {
+    for it in items {
+        let (guid, _) = item_guid(it, base);
+        if !written.insert(guid.clone()) { continue; }
+        let json = item_json(it, &guid);
+        if pretty {
+            writeln!(out, "{}", serde_json::to_string_pretty(&json)?)?;
+        } else {
+            writeln!(out, "{}", json)?;
+        }
+        out.flush()?;
+    }
+    Ok(())
+}
+
+// Width available for --format table: the terminal's own width, else $COLUMNS on a terminal
+// (shells rarely export it), otherwise a fixed 120.
+fn table_width() -> usize {
+    use std::io::IsTerminal;
+    if !io::stdout().is_terminal() { return 120; }
+    terminal_columns()
+        .or_else(|| env::var("COLUMNS").ok().and_then(|c| c.trim().parse::<usize>().ok()))
+        .map_or(120, |cols| cols.max(60))
+}
+
+// Columns of the terminal on stdout, asked of the tty driver.
+#[cfg(unix)]
+fn terminal_columns() -> Option<usize> {
+    let mut size = libc::winsize { ws_row: 0, ws_col: 0, ws_xpixel: 0, ws_ypixel: 0 };
+    // SAFETY: TIOCGWINSZ only writes a winsize into the struct we pass
+    let ok = unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) } == 0;
+    (ok && size.ws_col > 0).then_some(size.ws_col as usize)
+}
+
+#[cfg(not(unix))]
+fn terminal_columns() -> Option<usize> {
+    None
+}
+
+// Truncate to `width` characters, ending with an ellipsis when shortened.
+fn truncate_chars(s: &str, width: usize) -> String {
+    if s.chars().count() <= width { return s.to_string(); }
+    let mut out: String = s.chars().take(width.saturating_sub(1)).collect();
+    out.push('…');
+    out
+}
+
+// Human-readable table: index, title, date, link, plus a summary footer.
+fn write_table(items: &[Item]) -> Result<(), Box<dyn Error>> {
+    const IDX_W: usize = 4;
+    const DATE_W: usize = 16;
+    let rest = table_width().saturating_sub(IDX_W + DATE_W + 9);
+    let title_w = rest / 2;
+    let link_w = rest - title_w;
+
+    let mut out = stdout_writer();
+    writeln!(out, "{:>IDX_W$} | {:<title_w$} | {:<DATE_W$} | Link", "#", "Title", "Date")?;
+    writeln!(out, "{}", "-".repeat(IDX_W + title_w + DATE_W + link_w + 9))?;
+    for (i, it) in items.iter().enumerate() {
+        let date = it.pub_date.as_deref()
+            .map(|d| parse_pub_date(d).map(|dt| dt.format("%Y-%m-%d %H:%M").to_string()).unwrap_or_else(|| d.to_string()))
+            .unwrap_or_default();
+        writeln!(out, "{:>IDX_W$} | {:<title_w$} | {:<DATE_W$} | {}",
+            i + 1, truncate_chars(&it.title, title_w), truncate_chars(&date, DATE_W), truncate_chars(&it.link, link_w))?;
+    }
+    let sources: HashSet<String> = items.iter()
+        .filter_map(|it| Url::parse(&it.link).ok().and_then(|u| u.host_str().map(|h| h.to_string())))
+        .collect();
+    let mut sources: Vec<String> = sources.into_iter().collect();
+    sources.sort();
+    writeln!(out, "\n{} item(s) from {} source(s): {}", items.len(), sources.len(), sources.join(", "))?;
+    out.flush()?;
+    Ok(())
+}
+
+// 64-bit FNV-1a: tiny and, unlike std's DefaultHasher, stable across Rust releases and runs.
+fn fnv1a64(data: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for b in data {
+        hash ^= *b as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+// Item GUID and whether it is a permalink. The GUID never depends on the title, so headline
+// edits after publication don't make readers show the item twice: it is the canonical link
+// when that is an http(s) URL, otherwise a hash of the first stable field (link, date, image).
+// The one exception is items whose link fell back to the channel URL: the link identifies
+// nothing, so they get a hash of their content instead of all sharing one permalink.
+fn item_guid(it: &Item, base: &Url) -> (String, bool) {
+    if let Some(guid) = &it.guid {
+        return (guid.clone(), Url::parse(guid).is_ok_and(|u| matches!(u.scheme(), "http" | "https")));
+    }
+    let canon = canonicalize_url_str(it.link.trim());
+    if canon == canonicalize_url_str(base.as_str()) {
+        let content = format!("{}\n{}", it.title, it.description.as_deref().unwrap_or(""));
+        return (format!("urn:html2rss:{:016x}", fnv1a64(content.as_bytes())), false);
+    }
+    if let Ok(u) = Url::parse(&canon) {
+        if u.scheme() == "http" || u.scheme() == "https" {
+            return (canon, true);
+        }
+    }
+    let stable = [Some(canon.as_str()), it.pub_date.as_deref(), it.image.as_deref()]
+        .into_iter()
+        .flatten()
+        .find(|s| !s.is_empty())
+        .unwrap_or(it.title.as_str());
+    (format!("urn:html2rss:{:016x}", fnv1a64(stable.as_bytes())), false)
+}
+
+// pubDate (RFC 2822, the raw string when unparseable) and dc:date (ISO 8601, only when the
+// date parses) for one item date, both from the same parsed value so they always agree.
+fn item_date_values(date: &str) -> (String, Option<String>) {
+    let parsed = parse_pub_date(date);
+    (parsed.map_or_else(|| date.to_string(), |dt| dt.to_rfc2822()), parsed.map(|dt| dt.to_rfc3339()))
+}
+
+// Serialize one <item>.
+fn write_item<W: Write>(w: &mut Writer<W>, it: &Item, base: &Url) -> Result<(), Box<dyn Error>> {
+    w.write_event(Event::Start(BytesStart::new("item")))?;
+    write_text_element(w, "title", &it.title)?;
+    write_text_element(w, "link", &it.link)?;
+    let (guid, permalink) = item_guid(it, base);
+    let mut g = BytesStart::new("guid");
+    g.push_attribute(("isPermaLink", if permalink { "true" } else { "false" }));
+    w.write_event(Event::Start(g))?;
+    w.write_event(Event::Text(BytesText::new(&sanitize_text(&guid))))?;
+    w.write_event(Event::End(BytesEnd::new("guid")))?;
+    if let Some(desc) = &it.description {
+        if opts().cdata_descriptions {
+            write_cdata_element(w, "description", desc)?;
+        } else {
+            write_text_element(w, "description", desc)?;
+        }
+    }
+    if let Some(date) = &it.pub_date {
+        let (pub_date, dc_date) = item_date_values(date);
+        write_text_element(w, "pubDate", &pub_date)?;
+        if let (true, Some(dc)) = (opts().dublin_core, dc_date) {
+            write_text_element(w, "dc:date", &dc)?;
+        }
+    }
+    for cat in &it.categories {
+        write_text_element(w, "category", cat)?;
+    }
+    if let (true, Some(orig)) = (opts().keep_original_link, &it.original_link) {
+        write_text_element(w, "dc:source", orig)?;
+    }
+    if let (true, Some(lang)) = (opts().per_item_language, &it.language) {
+        write_text_element(w, "dc:language", lang)?;
+    }
+    if let (true, Some(upd)) = (opts().atom_updated, &it.updated) {
+        let value = parse_pub_date(upd).map(|dt| dt.to_rfc3339()).unwrap_or_else(|| upd.clone());
+        write_text_element(w, "atom:updated", &value)?;
+    }
+    if let (true, Some(audio)) = (opts().podcast, &it.audio) {
+        // episode audio is the enclosure; RSS requires length and type here
+        let mut enc = BytesStart::new("enclosure");
+        enc.push_attribute(("url", audio.as_str()));
+        enc.push_attribute(("length", it.audio_length.unwrap_or(0).to_string().as_str()));
+        enc.push_attribute(("type", it.audio_type.as_deref().unwrap_or("audio/mpeg")));
+        w.write_event(Event::Empty(enc))?;
+    } else if let Some(video) = &it.video {
+        // card player stream: the video itself
+        let mut enc = BytesStart::new("enclosure");
+        enc.push_attribute(("url", video.as_str()));
+        match it.video_length {
+            Some(len) => enc.push_attribute(("length", len.to_string().as_str())),
+            None if opts().enclosure_zero_length => enc.push_attribute(("length", "0")),
+            None => (),
+        }
+        enc.push_attribute(("type", it.video_type.as_deref().unwrap_or("video/mp4")));
+        w.write_event(Event::Empty(enc))?;
+    } else if let Some(img) = &it.image {
+        // include image as enclosure when available
+        let mut enc = BytesStart::new("enclosure");
+        enc.push_attribute(("url", img.as_str()));
+        match it.image_length {
+            Some(len) => enc.push_attribute(("length", len.to_string().as_str())),
+            None if opts().enclosure_zero_length => enc.push_attribute(("length", "0")),
+            None => (),
+        }
+        // leave type unspecified; some readers accept enclosure without type
+        w.write_event(Event::Empty(enc))?;
+    }
+    // remaining gallery images as Media RSS content
+    if opts().media_rss {
+        if let Some(player) = &it.player {
+            let mut mp = BytesStart::new("media:player");
+            mp.push_attribute(("url", player.as_str()));
+            w.write_event(Event::Empty(mp))?;
+        }
+        // the primary image too when the enclosure is the card's video, or to carry its caption
+        let captioned = it.image_caption.is_some() || it.image_credit.is_some();
+        for extra in it.images.iter().filter(|u| it.video.is_some() || captioned || it.image.as_ref() != Some(*u)) {
+            let mut mc = BytesStart::new("media:content");
+            mc.push_attribute(("url", extra.as_str()));
+            mc.push_attribute(("medium", "image"));
+            if !captioned || it.image.as_ref() != Some(extra) {
+                w.write_event(Event::Empty(mc))?;
+                continue;
+            }
+            w.write_event(Event::Start(mc))?;
+            if let Some(caption) = &it.image_caption {
+                let mut desc = BytesStart::new("media:description");
+                desc.push_attribute(("type", "plain"));
+                w.write_event(Event::Start(desc))?;
+                w.write_event(Event::Text(BytesText::new(&sanitize_text(caption))))?;
+                w.write_event(Event::End(BytesEnd::new("media:description")))?;
+            }
+            if let Some(credit) = &it.image_credit {
+                write_text_element(w, "media:credit", credit)?;
+            }
+            w.write_event(Event::End(BytesEnd::new("media:content")))?;
+        }
+        // author avatar and publisher logo, told apart by the media:credit role
+        for (img, role, name) in [
+            (&it.author_image, "author", &it.author),
+            (&it.publisher_logo, "publisher", &it.publisher),
+        ] {
+            let Some(img) = img else { continue };
+            let mut mc = BytesStart::new("media:content");
+            mc.push_attribute(("url", img.as_str()));
+            mc.push_attribute(("medium", "image"));
+            w.write_event(Event::Start(mc))?;
+            let mut credit = BytesStart::new("media:credit");
+            credit.push_attribute(("role", role));
+            w.write_event(Event::Start(credit))?;
+            w.write_event(Event::Text(BytesText::new(&sanitize_text(name.as_deref().unwrap_or("")))))?;
+            w.write_event(Event::End(BytesEnd::new("media:credit")))?;
+            w.write_event(Event::End(BytesEnd::new("media:content")))?;
+        }
+    }
+    if opts().podcast { write_itunes_item_tags(w, it)?; }
+    w.write_event(Event::End(BytesEnd::new("item")))?;
+    Ok(())
+}
+
+// Per-episode itunes: tags, each only when the data exists.
+fn write_itunes_item_tags<W: Write>(w: &mut Writer<W>, it: &Item) -> Result<(), Box<dyn Error>> {
+    if let Some(author) = &it.author {
+        write_text_element(w, "itunes:author", author)?;
     }
-    if let Ok(dt) = DateTime::parse_from_rfc2822(raw) {
-        return dt.to_rfc2822();
+    if let Some(desc) = &it.description {
+        write_text_element(w, "itunes:summary", desc)?;
     }
-    raw.to_string()
+    if let Some(img) = &it.image {
+        let mut tag = BytesStart::new("itunes:image");
+        tag.push_attribute(("href", img.as_str()));
+        w.write_event(Event::Empty(tag))?;
+    }
+    if let Some(d) = it.duration.as_deref().and_then(itunes_duration) {
+        write_text_element(w, "itunes:duration", &d)?;
+    }
+    if let Some(n) = it.episode {
+        write_text_element(w, "itunes:episode", &n.to_string())?;
+    }
+    Ok(())
+}
+
+static RE_ISO_DURATION: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)^P(?:(\d+)D)?(?:T(?:(\d+)H)?(?:(\d+)M)?(?:(\d+)(?:\.\d+)?S)?)?$").unwrap());
+
+// itunes:duration as hh:mm:ss from an ISO 8601 duration ("PT1H2M3S") or plain seconds;
+// an already clock-style value ("42:10") is kept.
+fn itunes_duration(raw: &str) -> Option<String> {
+    let raw = raw.trim();
+    let secs = if let Ok(n) = raw.parse::<u64>() {
+        n
+    } else if let Some(c) = RE_ISO_DURATION.captures(raw) {
+        let part = |i: usize| c.get(i).and_then(|m| m.as_str().parse::<u64>().ok()).unwrap_or(0);
+        part(1) * 86400 + part(2) * 3600 + part(3) * 60 + part(4)
+    } else if raw.split(':').all(|p| !p.is_empty() && p.chars().all(|c| c.is_ascii_digit())) {
+        return Some(raw.to_string());
+    } else {
+        return None;
+    };
+    if secs == 0 { return None; }
+    Some(format!("{:02}:{:02}:{:02}", secs / 3600, secs % 3600 / 60, secs % 60))
+}
+
+fn write_rss(base: &Url, channel: &Channel, items: &[Item]) -> Result<(), Box<dyn Error>> {
+    emit_document(&transcode_xml(rss_document(base, channel, items)?, output_encoding()))
 }
 
-fn write_rss(base: &Url, items: &Vec<Item>) -> Result<(), Box<dyn Error>> {
+// The RSS 2.0 document, UTF-8 encoded.
+fn rss_document(base: &Url, channel: &Channel, items: &[Item]) -> Result<Vec<u8>, Box<dyn Error>> {
     let mut writer = Writer::new_with_indent(Vec::new(), b' ', 2);
-    writer.write_event(Event::Decl(BytesDecl::new("1.0", Some("utf-8"), None)))?;
+    writer.write_event(Event::Decl(xml_decl(output_encoding())))?;
+    if opts().validation_comments { write_validation_comments(&mut writer, base, items)?; }
 
     // write <rss version="2.0">
     let mut rss_start = BytesStart::new("rss");
     rss_start.push_attribute(("version", "2.0"));
+    if opts().media_rss {
+        rss_start.push_attribute(("xmlns:media", "http://search.yahoo.com/mrss/"));
+    }
+    if opts().dublin_core || opts().keep_original_link || opts().per_item_language {
+        rss_start.push_attribute(("xmlns:dc", "http://purl.org/dc/elements/1.1/"));
+    }
+    if opts().podcast {
+        rss_start.push_attribute(("xmlns:itunes", "http://www.itunes.com/dtds/podcast-1.0.dtd"));
+    }
+    if opts().atom_updated {
+        rss_start.push_attribute(("xmlns:atom", "http://www.w3.org/2005/Atom"));
+    }
     writer.write_event(Event::Start(rss_start))?;
     writer.write_event(Event::Start(BytesStart::new("channel")))?;
-    write_text_element(&mut writer, "title", &format!("Feed for {}", base.host_str().unwrap_or(base.as_str())))?;
+    write_text_element(&mut writer, "title", &channel.title(base))?;
+    write_text_element(&mut writer, "link", base.as_str())?;
+    write_text_element(&mut writer, "description", "Generated by html2rss")?;
+    if let Some(c) = &channel.copyright {
+        write_text_element(&mut writer, "copyright", c)?;
+    }
+    if let Some(logo) = &channel.logo {
+        writer.write_event(Event::Start(BytesStart::new("image")))?;
+        write_text_element(&mut writer, "url", logo)?;
+        write_text_element(&mut writer, "title", &channel.title(base))?;
+        write_text_element(&mut writer, "link", base.as_str())?;
+        writer.write_event(Event::End(BytesEnd::new("image")))?;
+    }
+    if let Some(editor) = opts().managing_editor.as_ref().or(channel.managing_editor.as_ref()) {
+        write_text_element(&mut writer, "managingEditor", editor)?;
+    }
+    if let Some(web_master) = &opts().web_master {
+        write_text_element(&mut writer, "webMaster", web_master)?;
+    }
+    if opts().podcast {
+        if let Some(author) = &channel.publisher {
+            write_text_element(&mut writer, "itunes:author", author)?;
+        }
+        if let Some(img) = &channel.image {
+            let mut tag = BytesStart::new("itunes:image");
+            tag.push_attribute(("href", img.as_str()));
+            writer.write_event(Event::Empty(tag))?;
+        }
+    }
+    let mut categories = HashSet::new();
+    for cat in opts().channel_category.iter().filter(|c| categories.insert(c.to_lowercase())) {
+        write_text_element(&mut writer, "category", cat)?;
+    }
+
+    // every text value is sanitized on the way in, so no item's content can fail the write
+    for it in items {
+        write_item(&mut writer, it, base)?;
+    }
+
+    writer.write_event(Event::End(BytesEnd::new("channel")))?;
+    writer.write_event(Event::End(BytesEnd::new("rss")))?;
+
+    Ok(writer.into_inner())
+}
+
+// RSS 1.0: rdf:RDF root, channel with an rdf:Seq of item resources, then the items themselves.
+fn write_rss10(base: &Url, channel: &Channel, items: &[Item]) -> Result<(), Box<dyn Error>> {
+    emit_document(&transcode_xml(rss10_document(base, channel, items)?, output_encoding()))
+}
+
+// The RSS 1.0 (RDF) document, UTF-8 encoded.
+fn rss10_document(base: &Url, channel: &Channel, items: &[Item]) -> Result<Vec<u8>, Box<dyn Error>> {
+    let mut writer = Writer::new_with_indent(Vec::new(), b' ', 2);
+    writer.write_event(Event::Decl(xml_decl(output_encoding())))?;
+    if opts().validation_comments { write_validation_comments(&mut writer, base, items)?; }
+
+    let mut root = BytesStart::new("rdf:RDF");
+    root.push_attribute(("xmlns:rdf", "http://www.w3.org/1999/02/22-rdf-syntax-ns#"));
+    root.push_attribute(("xmlns", "http://purl.org/rss/1.0/"));
+    root.push_attribute(("xmlns:dc", "http://purl.org/dc/elements/1.1/"));
+    writer.write_event(Event::Start(root))?;
+
+    let mut channel_start = BytesStart::new("channel");
+    channel_start.push_attribute(("rdf:about", base.as_str()));
+    writer.write_event(Event::Start(channel_start))?;
+    write_text_element(&mut writer, "title", &channel.title(base))?;
     write_text_element(&mut writer, "link", base.as_str())?;
     write_text_element(&mut writer, "description", "Generated by html2rss")?;
+    writer.write_event(Event::Start(BytesStart::new("items")))?;
+    writer.write_event(Event::Start(BytesStart::new("rdf:Seq")))?;
+    for it in items {
+        let mut li = BytesStart::new("rdf:li");
+        li.push_attribute(("rdf:resource", it.link.as_str()));
+        writer.write_event(Event::Empty(li))?;
+    }
+    writer.write_event(Event::End(BytesEnd::new("rdf:Seq")))?;
+    writer.write_event(Event::End(BytesEnd::new("items")))?;
+    writer.write_event(Event::End(BytesEnd::new("channel")))?;
 
     for it in items {
-        writer.write_event(Event::Start(BytesStart::new("item")))?;
+        let mut item = BytesStart::new("item");
+        item.push_attribute(("rdf:about", it.link.as_str()));
+        writer.write_event(Event::Start(item))?;
         write_text_element(&mut writer, "title", &it.title)?;
         write_text_element(&mut writer, "link", &it.link)?;
         if let Some(desc) = &it.description {
             write_text_element(&mut writer, "description", desc)?;
         }
-        if let Some(date) = &it.pub_date {
-            write_text_element(&mut writer, "pubDate", &format_pub_date(date))?;
-        }
-        // include image as enclosure when available
-        if let Some(img) = &it.image {
-            let mut enc = BytesStart::new("enclosure");
-            enc.push_attribute(("url", img.as_str()));
-            // leave type unspecified; some readers accept enclosure without type
-            writer.write_event(Event::Empty(enc))?;
+        if let Some(dt) = it.pub_date.as_deref().and_then(parse_pub_date) {
+            write_text_element(&mut writer, "dc:date", &dt.to_rfc3339())?;
         }
         writer.write_event(Event::End(BytesEnd::new("item")))?;
     }
 
-    writer.write_event(Event::End(BytesEnd::new("channel")))?;
-    writer.write_event(Event::End(BytesEnd::new("rss")))?;
+    writer.write_event(Event::End(BytesEnd::new("rdf:RDF")))?;
+    Ok(writer.into_inner())
+}
+
+fn output_encoding() -> &'static Encoding {
+    opts().output_encoding.unwrap_or(UTF_8)
+}
+
+fn xml_decl(enc: &'static Encoding) -> BytesDecl<'static> {
+    let name = if enc == UTF_8 { "utf-8" } else { enc.name() };
+    BytesDecl::new("1.0", Some(name), None)
+}
+
+// Re-encode the UTF-8 serialized XML for --output-encoding. Unmappable characters come out
+// as &#NNNN; references, which XML readers resolve in text and attribute values alike. CDATA
+// sections and comments take no references: there the section is closed around the reference
+// (--cdata-descriptions), and a comment gets "?" (--validation-comments).
+fn transcode_xml(xml: Vec<u8>, enc: &'static Encoding) -> Vec<u8> {
+    if enc == UTF_8 { return xml; }
+    let text = String::from_utf8_lossy(&xml);
+    let unmappable = |c: char| enc.encode(c.encode_utf8(&mut [0; 4])).2;
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text.as_ref();
+    while let Some(start) = rest.find("<![CDATA[").into_iter().chain(rest.find("<!--")).min() {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let (open, close) = if rest.starts_with("<!--") { ("<!--", "-->") } else { ("<![CDATA[", "]]>") };
+        let end = rest.find(close).map_or(rest.len(), |i| i + close.len());
+        for c in rest[..end].chars() {
+            match (unmappable(c), open) {
+                (false, _) => out.push(c),
+                (true, "<!--") => out.push('?'),
+                (true, _) => out.push_str(&format!("]]>&#{};<![CDATA[", c as u32)),
+            }
+        }
+        rest = &rest[end..];
+    }
+    out.push_str(rest);
+    let (bytes, _, _) = enc.encode(&out);
+    bytes.into_owned()
+}
+
+// --ca-cert: PEM (every certificate of a bundle) or a single DER certificate.
+fn load_ca_certs(path: &Path) -> Result<Vec<reqwest::Certificate>, Box<dyn Error>> {
+    let bytes = fs::read(path).map_err(|e| format!("reading CA certificate {}: {}", path.display(), e))?;
+    let certs = if bytes.windows(10).any(|w| w == b"-----BEGIN") {
+        reqwest::Certificate::from_pem_bundle(&bytes)
+    } else {
+        reqwest::Certificate::from_der(&bytes).map(|c| vec![c])
+    };
+    match certs {
+        Ok(certs) if !certs.is_empty() => Ok(certs),
+        Ok(_) => Err(format!("no certificates in {}", path.display()).into()),
+        Err(e) => Err(format!("invalid CA certificate {}: {}", path.display(), e).into()),
+    }
+}
+
+// Buffered stdout (--output-buffer); callers decide when to flush.
+fn stdout_writer() -> io::BufWriter<io::StdoutLock<'static>> {
+    io::BufWriter::with_capacity(opts().output_buffer.max(1), io::stdout().lock())
+}
 
-    let out = writer.into_inner();
-    io::stdout().write_all(&out)?;
-    io::stdout().write_all(b"\n")?;
-    io::stdout().flush()?;
+// Write a serialized document to stdout with a trailing newline, flushed once.
+fn emit_document(doc: &[u8]) -> Result<(), Box<dyn Error>> {
+    let mut out = stdout_writer();
+    out.write_all(doc)?;
+    out.write_all(b"\n")?;
+    out.flush()?;
     Ok(())
 }